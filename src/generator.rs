@@ -1,40 +1,361 @@
+use crate::normal_game::budget::Budget;
+use crate::normal_game::budget::GaveUp;
+use crate::normal_game::cage::Cage;
 use crate::normal_game::cell::Position;
+use crate::normal_game::difficulty::Difficulty;
 use crate::normal_game::setting::BlockSize;
 use crate::normal_game::setting::GameSetting;
 use crate::normal_game::NormalGame;
 use crate::pattern::AnswerPattern;
 use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+
+/// The number of solved games tried by `generate_with_difficulty` before giving up.
+const DIFFICULTY_ATTEMPTS: usize = 100;
+
+/// The maximum number of cells in a cage generated by `generate_killer`.
+const MAX_KILLER_CAGE_SIZE: usize = 4;
 
 impl NormalGame {
     pub fn generate(block_size: BlockSize) -> (NormalGame, NormalGame) {
-        let solved_game = Self::generate_random_solved_game(block_size);
-        let game = Self::to_issue(&solved_game);
-        (game, solved_game)
+        Self::generate_with_rng(block_size, &mut thread_rng())
+    }
+
+    /// Generate an issue and its answer from the seed.
+    /// The same block_size and seed always yield the same issue and answer.
+    /// It uses ChaCha8Rng, whose output is the same on every platform and version of rand,
+    /// unlike StdRng, whose algorithm may change.
+    ///
+    /// シードから問題とその答えを生成する。
+    /// block_size とシードが同じであれば、常に同じ問題と答えが生成される。
+    /// アルゴリズムが変わる可能性のある StdRng と異なり、全てのプラットフォームと rand のバージョンで
+    /// 同じ出力となる ChaCha8Rng を使用する。
+    pub fn generate_with_seed(block_size: BlockSize, seed: u64) -> (NormalGame, NormalGame) {
+        Self::generate_with_rng(block_size, &mut ChaCha8Rng::seed_from_u64(seed))
+    }
+
+    /// Generate an issue and its answer with the specified random number generator.
+    ///
+    /// 指定した乱数生成器で問題とその答えを生成する。
+    pub fn generate_with_rng<R: Rng + ?Sized>(
+        block_size: BlockSize,
+        rng: &mut R,
+    ) -> (NormalGame, NormalGame) {
+        // A game without variants always has a solution.
+        Self::generate_with_setting_and_rng(&GameSetting::new(block_size), rng).unwrap()
+    }
+
+    /// Generate an issue and its answer of the setting, which may be a variant such as Sudoku X.
+    /// It returns None if the setting has no solution, for example Anti-King on a 4x4 board.
+    ///
+    /// 指定した設定の問題とその答えを生成する。設定は対角線ナンプレなどのバリアントでもよい。
+    /// 4x4 の盤面のアンチキングなど、設定に解答が存在しない場合は None を返却する。
+    pub fn generate_with_setting(setting: &GameSetting) -> Option<(NormalGame, NormalGame)> {
+        Self::generate_with_setting_and_rng(setting, &mut thread_rng())
+    }
+
+    /// Generate an issue and its answer of the setting with the specified random number generator.
+    /// It returns None if the setting has no solution.
+    ///
+    /// 指定した乱数生成器で、指定した設定の問題とその答えを生成する。
+    /// 設定に解答が存在しない場合は None を返却する。
+    pub fn generate_with_setting_and_rng<R: Rng + ?Sized>(
+        setting: &GameSetting,
+        rng: &mut R,
+    ) -> Option<(NormalGame, NormalGame)> {
+        let solved_game = Self::generate_random_solved_game(setting, rng)?;
+        let game = Self::to_issue(&solved_game, rng);
+        Some((game, solved_game))
+    }
+
+    /// Generate an issue and its answer within the budget.
+    /// It returns Err(GaveUp) if the budget has run out.
+    ///
+    /// 上限の範囲で問題とその答えを生成する。
+    /// 上限に達した場合は Err(GaveUp) を返却する。
+    pub fn generate_with_budget(
+        block_size: BlockSize,
+        budget: &Budget,
+    ) -> Result<(NormalGame, NormalGame), GaveUp> {
+        Self::generate_with_budget_and_rng(block_size, budget, &mut thread_rng())
+    }
+
+    /// Generate an issue and its answer within the budget with the specified random number generator.
+    /// If the budget does not run out, the result is the same as `generate_with_rng`.
+    ///
+    /// 指定した乱数生成器で、上限の範囲で問題とその答えを生成する。
+    /// 上限に達しなかった場合、結果は `generate_with_rng` と同じである。
+    pub fn generate_with_budget_and_rng<R: Rng + ?Sized>(
+        block_size: BlockSize,
+        budget: &Budget,
+        rng: &mut R,
+    ) -> Result<(NormalGame, NormalGame), GaveUp> {
+        // A game without variants always has a solution.
+        Self::generate_with_setting_and_budget_and_rng(&GameSetting::new(block_size), budget, rng)
+            .map(|generated| generated.unwrap())
+    }
+
+    /// Generate an issue and its answer of the setting within the budget.
+    /// It returns Ok(None) if the setting has no solution, and Err(GaveUp) if the budget has run out.
+    ///
+    /// 上限の範囲で、指定した設定の問題とその答えを生成する。
+    /// 設定に解答が存在しない場合は Ok(None) を、上限に達した場合は Err(GaveUp) を返却する。
+    pub fn generate_with_setting_and_budget(
+        setting: &GameSetting,
+        budget: &Budget,
+    ) -> Result<Option<(NormalGame, NormalGame)>, GaveUp> {
+        Self::generate_with_setting_and_budget_and_rng(setting, budget, &mut thread_rng())
+    }
+
+    /// Generate an issue and its answer of the setting within the budget with the specified random number generator.
+    /// If the budget does not run out, the result is the same as `generate_with_setting_and_rng`.
+    ///
+    /// 指定した乱数生成器で、上限の範囲で、指定した設定の問題とその答えを生成する。
+    /// 上限に達しなかった場合、結果は `generate_with_setting_and_rng` と同じである。
+    pub fn generate_with_setting_and_budget_and_rng<R: Rng + ?Sized>(
+        setting: &GameSetting,
+        budget: &Budget,
+        rng: &mut R,
+    ) -> Result<Option<(NormalGame, NormalGame)>, GaveUp> {
+        let solved_game = match Self::generate_random_solved_game_within(setting, rng, budget)? {
+            Some(solved_game) => solved_game,
+            None => return Ok(None),
+        };
+        let game = Self::to_issue_within(&solved_game, rng, budget)?;
+        Ok(Some((game, solved_game)))
+    }
+
+    /// Generate an issue of Killer Sudoku and its answer.
+    /// The whole board is divided into cages, and the answers are given only where the cages are not enough
+    /// to make the solution unique, so the issue has few or no answers.
+    ///
+    /// キラーナンプレの問題とその答えを生成する。
+    /// 盤面全体をケージに分割し、ケージだけでは解答が一意にならない箇所にのみ解答を与えるため、
+    /// 問題の解答は少ないか、全くない。
+    ///
+    /// It returns None if no solved game of the block size can be made.
+    ///
+    /// ブロックサイズの解答済みのゲームを作成できない場合は None を返却する。
+    pub fn generate_killer(block_size: BlockSize) -> Option<(NormalGame, NormalGame)> {
+        Self::generate_killer_with_rng(block_size, &mut thread_rng())
+    }
+
+    /// Generate an issue of Killer Sudoku and its answer with the specified random number generator.
+    ///
+    /// 指定した乱数生成器でキラーナンプレの問題とその答えを生成する。
+    pub fn generate_killer_with_rng<R: Rng + ?Sized>(
+        block_size: BlockSize,
+        rng: &mut R,
+    ) -> Option<(NormalGame, NormalGame)> {
+        Self::generate_killer_with_budget_and_rng(block_size, &Budget::new(), rng).unwrap()
+    }
+
+    /// Generate an issue of Killer Sudoku and its answer within the budget.
+    /// It returns Ok(None) if no solved game of the block size can be made, and Err(GaveUp) if the budget has run out.
+    ///
+    /// 上限の範囲でキラーナンプレの問題とその答えを生成する。
+    /// ブロックサイズの解答済みのゲームを作成できない場合は Ok(None) を、上限に達した場合は Err(GaveUp) を返却する。
+    pub fn generate_killer_with_budget(
+        block_size: BlockSize,
+        budget: &Budget,
+    ) -> Result<Option<(NormalGame, NormalGame)>, GaveUp> {
+        Self::generate_killer_with_budget_and_rng(block_size, budget, &mut thread_rng())
+    }
+
+    /// Generate an issue of Killer Sudoku and its answer within the budget with the specified random number generator.
+    /// If the budget does not run out, the result is the same as `generate_killer_with_rng`.
+    ///
+    /// 指定した乱数生成器で、上限の範囲でキラーナンプレの問題とその答えを生成する。
+    /// 上限に達しなかった場合、結果は `generate_killer_with_rng` と同じである。
+    pub fn generate_killer_with_budget_and_rng<R: Rng + ?Sized>(
+        block_size: BlockSize,
+        budget: &Budget,
+        rng: &mut R,
+    ) -> Result<Option<(NormalGame, NormalGame)>, GaveUp> {
+        let solved = match Self::generate_random_solved_game_within(
+            &GameSetting::new(block_size),
+            rng,
+            budget,
+        )? {
+            Some(solved) => solved,
+            None => return Ok(None),
+        };
+        let cages = Self::create_random_cages(&solved, rng);
+        let setting = solved.setting().clone().with_cages(cages).unwrap();
+        let mut game = NormalGame::new(setting.clone());
+        let mut solved_game = NormalGame::new(setting);
+        for cell in solved.cells().iter() {
+            solved_game.set_answer(cell.pos(), cell.answer().unwrap());
+        }
+        // Give an answer where two solutions differ until the solution is unique.
+        loop {
+            let solutions = game.solutions_with_dlx_within(2, budget)?;
+            if solutions.len() < 2 {
+                break;
+            }
+            let differences: Vec<Position> = solutions[0]
+                .cells()
+                .iter()
+                .zip(solutions[1].cells().iter())
+                .filter(|(a, b)| a.answer() != b.answer())
+                .map(|(a, _)| a.pos())
+                .collect();
+            let pos = differences[rng.gen_range(0..differences.len())];
+            let answer = solved_game.find_cell(pos).unwrap().answer().unwrap();
+            game.set_answer(pos, answer);
+        }
+        Ok(Some((game, solved_game)))
+    }
+
+    /// Divide the board into connected cages that have different answers of the solved game.
+    ///
+    /// 盤面を、解答済みのゲームの解答が互いに異なる連結したケージに分割する。
+    fn create_random_cages<R: Rng + ?Sized>(solved_game: &NormalGame, rng: &mut R) -> Vec<Cage> {
+        let side_size = solved_game.setting().side_size();
+        let cells = solved_game.cells();
+        let mut caged = vec![false; cells.len()];
+        let mut order: Vec<usize> = (0..cells.len()).collect();
+        order.shuffle(rng);
+        let mut cages: Vec<Cage> = vec![];
+        for start in order {
+            if caged[start] {
+                continue;
+            }
+            caged[start] = true;
+            let mut cage = vec![start];
+            let size = rng.gen_range(2..=MAX_KILLER_CAGE_SIZE);
+            while cage.len() < size {
+                let neighbours: Vec<usize> = cage
+                    .iter()
+                    .flat_map(|i| {
+                        let pos = cells[*i].pos();
+                        [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                            .iter()
+                            .map(move |(dx, dy)| (pos.x() as i16 + dx, pos.y() as i16 + dy))
+                    })
+                    .filter(|(x, y)| {
+                        (0..side_size as i16).contains(x) && (0..side_size as i16).contains(y)
+                    })
+                    .map(|(x, y)| Position::new(x as u8, y as u8).index(side_size))
+                    .filter(|n| {
+                        !caged[*n]
+                            && cage
+                                .iter()
+                                .all(|i| cells[*i].answer() != cells[*n].answer())
+                    })
+                    .collect();
+                if neighbours.is_empty() {
+                    break;
+                }
+                let next = neighbours[rng.gen_range(0..neighbours.len())];
+                caged[next] = true;
+                cage.push(next);
+            }
+            let sum = cage
+                .iter()
+                .map(|i| cells[*i].answer().unwrap() as u32)
+                .sum();
+            cages.push(Cage::new(
+                sum,
+                cage.iter().map(|i| cells[*i].pos()).collect(),
+            ));
+        }
+        cages
     }
 
-    fn to_issue(solved_game: &NormalGame) -> NormalGame {
+    /// Generate an issue of the specified difficulty and its answer.
+    /// It keeps generating until an issue of the difficulty is found,
+    /// and returns None if it is not found within the budget.
+    ///
+    /// 指定した難易度の問題とその答えを生成する。
+    /// 指定した難易度の問題が見つかるまで生成を繰り返し、上限回数以内に見つからない場合は None を返却する。
+    pub fn generate_with_difficulty(
+        block_size: BlockSize,
+        difficulty: Difficulty,
+    ) -> Option<(NormalGame, NormalGame)> {
+        Self::generate_with_difficulty_and_rng(
+            block_size,
+            difficulty,
+            &mut thread_rng(),
+            DIFFICULTY_ATTEMPTS,
+        )
+    }
+
+    /// Generate an issue of the specified difficulty with the specified random number generator.
+    /// `max_attempts` is the number of solved games to try before giving up.
+    ///
+    /// 指定した乱数生成器で指定した難易度の問題を生成する。
+    /// `max_attempts` は諦めるまでに試す解答済みのゲームの数である。
+    pub fn generate_with_difficulty_and_rng<R: Rng + ?Sized>(
+        block_size: BlockSize,
+        difficulty: Difficulty,
+        rng: &mut R,
+        max_attempts: usize,
+    ) -> Option<(NormalGame, NormalGame)> {
+        for _ in 0..max_attempts {
+            // A game without variants always has a solution.
+            let solved_game =
+                Self::generate_random_solved_game(&GameSetting::new(block_size), rng).unwrap();
+            let game = Self::to_issue_with_difficulty(&solved_game, difficulty, rng);
+            if game.difficulty() == difficulty {
+                return Some((game, solved_game));
+            }
+        }
+        None
+    }
+
+    /// Remove the answers in random order as long as the game can be solved within the difficulty.
+    ///
+    /// 指定した難易度の範囲で解ける限り、ランダムな順序で解答を取り除く。
+    fn to_issue_with_difficulty<R: Rng + ?Sized>(
+        solved_game: &NormalGame,
+        difficulty: Difficulty,
+        rng: &mut R,
+    ) -> NormalGame {
+        let mut game = solved_game.clone();
+        let mut poslist: Vec<Position> = game.cells().iter().map(|c| c.pos()).collect();
+        poslist.shuffle(rng);
+        for pos in poslist {
+            let mut tmp_game = game.clone();
+            tmp_game.remove_answer(pos);
+            if tmp_game.is_solvable_within(difficulty) {
+                game = tmp_game;
+            }
+        }
+        game
+    }
+
+    fn to_issue<R: Rng + ?Sized>(solved_game: &NormalGame, rng: &mut R) -> NormalGame {
+        Self::to_issue_within(solved_game, rng, &Budget::new()).unwrap()
+    }
+
+    fn to_issue_within<R: Rng + ?Sized>(
+        solved_game: &NormalGame,
+        rng: &mut R,
+        budget: &Budget,
+    ) -> Result<NormalGame, GaveUp> {
         let mut game = solved_game.clone();
-        let mut rng = thread_rng();
         let mut count = 0;
         let mut trush: Vec<Position> = vec![];
         // let mut poslist: Vec<Position> = game.cells().iter().map(|c| c.borrow().pos()).collect();
         loop {
+            budget.check(0)?;
             let mut poslist: Vec<Position> = game
                 .groups()
                 .iter()
                 .reduce(|a, b| {
-                    if a.borrow().answer_count() < b.borrow().answer_count() {
+                    if a.answer_count(game.cells()) < b.answer_count(game.cells()) {
                         b
                     } else {
                         a
                     }
                 })
                 .unwrap()
-                .borrow()
                 .cells()
                 .iter()
-                .filter(|c| c.borrow().answer().is_some())
-                .map(|c| c.borrow().pos())
+                .map(|c| &game.cells()[*c])
+                .filter(|c| c.answer().is_some())
+                .map(|c| c.pos())
                 .filter(|p| trush.iter().find(|p2| *p == **p2).is_none())
                 .collect();
             if poslist.len() == 0 {
@@ -63,19 +384,41 @@ impl NormalGame {
                 }
             }
         }
-        game
+        Ok(game)
+    }
+
+    fn generate_random_solved_game<R: Rng + ?Sized>(
+        setting: &GameSetting,
+        rng: &mut R,
+    ) -> Option<NormalGame> {
+        Self::generate_random_solved_game_within(setting, rng, &Budget::new()).unwrap()
     }
 
-    fn generate_random_solved_game(block_size: BlockSize) -> NormalGame {
-        let setting = GameSetting::new(block_size);
+    /// Generate a solved game of the setting in random order within the budget.
+    /// It returns Ok(None) if the setting has no solution.
+    ///
+    /// 上限の範囲で、指定した設定の解答済みのゲームをランダムに生成する。
+    /// 設定に解答が存在しない場合は Ok(None) を返却する。
+    fn generate_random_solved_game_within<R: Rng + ?Sized>(
+        setting: &GameSetting,
+        rng: &mut R,
+        budget: &Budget,
+    ) -> Result<Option<NormalGame>, GaveUp> {
+        let block_size = setting.block_size();
         let mut answer_candidate = setting.answer_candidate();
         let mut random_sort_answer_candidate: Vec<u8> = Vec::new();
-        let mut rng = thread_rng();
         while answer_candidate.len() != 0 {
             let index = rng.gen_range(0..answer_candidate.len());
             random_sort_answer_candidate.push(answer_candidate.remove(index));
         }
 
+        // The patterns and the shuffle only keep the rows, columns and blocks valid,
+        // so the variants are solved from scratch in random order.
+        if setting.is_variant() {
+            let game = NormalGame::new(setting.with_answer_candidate(random_sort_answer_candidate));
+            return game.solve_with_dlx_randomly(rng, budget);
+        }
+
         if let Some(solved_str) = AnswerPattern::create_solved_string_from_pattern(
             &block_size,
             &random_sort_answer_candidate,
@@ -85,17 +428,15 @@ impl NormalGame {
                 random_sort_answer_candidate,
             ));
             game.load(&solved_str);
-            game.shuffle();
-            return game;
+            return Ok(Some(game.shuffle_with_rng(rng)));
         }
 
         let game = NormalGame::new(GameSetting::new_with_answer_candidate(
             block_size,
             random_sort_answer_candidate,
         ));
-        let solved_game = game.solve().unwrap();
-        solved_game.shuffle();
-        solved_game
+        let solved_game = game.solve_with_dlx_within(budget)?;
+        Ok(solved_game.map(|solved_game| solved_game.shuffle_with_rng(rng)))
     }
 }
 
@@ -126,6 +467,430 @@ mod tests {
             assert!(false);
         }
     }
+    mod generate_with_seed {
+        use super::*;
+        fn block_size() -> BlockSize {
+            BlockSize {
+                height: 3,
+                width: 3,
+            }
+        }
+        #[test]
+        fn same_seed_yields_same_game() {
+            let (issue1, solved1) = NormalGame::generate_with_seed(block_size(), 20211018);
+            let (issue2, solved2) = NormalGame::generate_with_seed(block_size(), 20211018);
+            assert_eq!(issue1.to_string(), issue2.to_string());
+            assert_eq!(solved1.to_string(), solved2.to_string());
+        }
+        #[test]
+        fn different_seeds_yield_different_games() {
+            let (_, solved1) = NormalGame::generate_with_seed(block_size(), 1);
+            let (_, solved2) = NormalGame::generate_with_seed(block_size(), 2);
+            assert_ne!(solved1.to_string(), solved2.to_string());
+        }
+        #[test]
+        fn it_keeps_the_game_of_the_seed() {
+            let (issue, solved) = NormalGame::generate_with_seed(block_size(), 20211018);
+            assert_eq!(issue.to_string(), "  9 3    | 5     4 |  3   6 5|    1    |2    78  |  64  3  |   5   91|5  1   3 |81   2   ");
+            assert_eq!(solved.to_string(), "649835127|758621943|123749685|485213769|231967854|976458312|367584291|592176438|814392576");
+        }
+        #[test]
+        fn issue_is_solved_to_the_answer() {
+            let (issue, solved) = NormalGame::generate_with_seed(block_size(), 3);
+            assert_eq!(issue.solve().unwrap().to_string(), solved.to_string());
+        }
+    }
+    mod generate_with_setting {
+        use super::*;
+        use crate::normal_game::relation::PeerRelation;
+        use crate::normal_game::strategy::SolveStrategy;
+        use crate::normal_game::GameState;
+        fn setting() -> GameSetting {
+            GameSetting::new(BlockSize {
+                height: 3,
+                width: 3,
+            })
+            .with_diagonals()
+        }
+        fn assert_diagonals_are_unique(game: &NormalGame) {
+            let diagonal = |f: &dyn Fn(u8) -> Position| {
+                let mut answers: Vec<u8> = (0..9)
+                    .map(|i| game.find_cell(f(i)).unwrap().answer().unwrap())
+                    .collect();
+                answers.sort_unstable();
+                answers
+            };
+            assert_eq!(
+                diagonal(&|i| Position::new(i, i)),
+                (1..=9).collect::<Vec<u8>>()
+            );
+            assert_eq!(
+                diagonal(&|i| Position::new(8 - i, i)),
+                (1..=9).collect::<Vec<u8>>()
+            );
+        }
+        #[test]
+        fn it_generates_diagonal_game() {
+            let (issue, solved) = NormalGame::generate_with_setting_and_rng(
+                &setting(),
+                &mut StdRng::seed_from_u64(1),
+            )
+            .unwrap();
+            let mut checked = solved.clone();
+            assert_eq!(checked.check_status(), GameState::Complete);
+            assert_diagonals_are_unique(&solved);
+            assert!(issue.has_unique_solution());
+            for strategy in [SolveStrategy::Backtracking, SolveStrategy::DancingLinks].iter() {
+                assert_eq!(
+                    issue.solve_with(*strategy).unwrap().to_string(),
+                    solved.to_string()
+                );
+            }
+        }
+        #[test]
+        fn it_generates_jigsaw_game() {
+            let regions: Vec<usize> = "000011222|000114222|301144222|303114555|333144885|363444885|366677885|666777855|667777885"
+                .split('|')
+                .flat_map(|row| row.chars().map(|c| c.to_digit(10).unwrap() as usize))
+                .collect();
+            let setting = GameSetting::new(BlockSize {
+                height: 3,
+                width: 3,
+            })
+            .with_regions(regions)
+            .unwrap();
+            let (issue, solved) =
+                NormalGame::generate_with_setting_and_rng(&setting, &mut StdRng::seed_from_u64(1))
+                    .unwrap();
+            let mut checked = solved.clone();
+            assert_eq!(checked.check_status(), GameState::Complete);
+            assert!(issue.has_unique_solution());
+            assert!(issue.rate().steps > 0);
+            let mut loaded = NormalGame::new(setting);
+            loaded.load(&issue.to_string());
+            for strategy in [
+                SolveStrategy::Backtracking,
+                SolveStrategy::Logical,
+                SolveStrategy::DancingLinks,
+            ]
+            .iter()
+            {
+                assert_eq!(
+                    loaded.solve_with(*strategy).unwrap().to_string(),
+                    solved.to_string()
+                );
+            }
+        }
+        #[test]
+        fn it_generates_windoku_game() {
+            let setting = GameSetting::new(BlockSize {
+                height: 3,
+                width: 3,
+            })
+            .with_windows();
+            let (issue, solved) =
+                NormalGame::generate_with_setting_and_rng(&setting, &mut StdRng::seed_from_u64(1))
+                    .unwrap();
+            let mut checked = solved.clone();
+            assert_eq!(checked.check_status(), GameState::Complete);
+            assert_eq!(solved.groups().len(), 27 + 4);
+            assert!(issue.has_unique_solution());
+            for strategy in [SolveStrategy::Backtracking, SolveStrategy::DancingLinks].iter() {
+                assert_eq!(
+                    issue.solve_with(*strategy).unwrap().to_string(),
+                    solved.to_string()
+                );
+            }
+        }
+        #[test]
+        fn it_generates_anti_knight_game() {
+            let setting = GameSetting::new(BlockSize {
+                height: 3,
+                width: 3,
+            })
+            .with_peer_relation(PeerRelation::AntiKnight);
+            let (issue, solved) =
+                NormalGame::generate_with_setting_and_rng(&setting, &mut StdRng::seed_from_u64(1))
+                    .unwrap();
+            let mut checked = solved.clone();
+            assert_eq!(checked.check_status(), GameState::Complete);
+            assert!(issue.has_unique_solution());
+            assert_eq!(
+                issue.solve_with_dlx().unwrap().to_string(),
+                solved.to_string()
+            );
+        }
+        #[test]
+        fn it_returns_none_for_the_setting_without_solution() {
+            let setting = GameSetting::new(BlockSize {
+                height: 2,
+                width: 2,
+            })
+            .with_peer_relation(PeerRelation::AntiKing);
+            assert!(NormalGame::generate_with_setting(&setting).is_none());
+        }
+        #[test]
+        fn it_generates_various_diagonal_games() {
+            let (_, solved1) = NormalGame::generate_with_setting_and_rng(
+                &setting(),
+                &mut StdRng::seed_from_u64(1),
+            )
+            .unwrap();
+            let (_, solved2) = NormalGame::generate_with_setting_and_rng(
+                &setting(),
+                &mut StdRng::seed_from_u64(2),
+            )
+            .unwrap();
+            assert_ne!(solved1.to_string(), solved2.to_string());
+        }
+        #[test]
+        fn it_generates_the_same_game_as_generate_with_seed() {
+            let block_size = BlockSize {
+                height: 3,
+                width: 3,
+            };
+            let (issue1, _) = NormalGame::generate_with_setting_and_rng(
+                &GameSetting::new(block_size),
+                &mut ChaCha8Rng::seed_from_u64(3),
+            )
+            .unwrap();
+            let (issue2, _) = NormalGame::generate_with_seed(block_size, 3);
+            assert_eq!(issue1.to_string(), issue2.to_string());
+        }
+    }
+    mod generate_killer {
+        use super::*;
+        use crate::normal_game::strategy::SolveStrategy;
+        use crate::normal_game::GameState;
+        fn block_size() -> BlockSize {
+            BlockSize {
+                height: 3,
+                width: 3,
+            }
+        }
+        #[test]
+        fn it_generates_a_unique_killer_issue_with_few_answers() {
+            let mut rng = StdRng::seed_from_u64(0);
+            let (issue, solved) =
+                NormalGame::generate_killer_with_rng(block_size(), &mut rng).unwrap();
+            let cages = issue.setting().cages();
+            assert_eq!(cages.iter().map(|c| c.cells().len()).sum::<usize>(), 81);
+            assert!(cages
+                .iter()
+                .all(|c| c.cells().len() <= MAX_KILLER_CAGE_SIZE));
+            assert!(issue.answered_count() < 20);
+            assert_eq!(issue.count_solutions_with_dlx(2), 1);
+            let mut answer = solved.clone();
+            assert_eq!(answer.check_status(), GameState::Complete);
+            for strategy in [SolveStrategy::Backtracking, SolveStrategy::DancingLinks].iter() {
+                assert!(issue.solve_with(*strategy).unwrap() == solved);
+            }
+        }
+        #[test]
+        fn it_keeps_the_cages_through_the_text() {
+            let mut rng = StdRng::seed_from_u64(1);
+            let (issue, solved) =
+                NormalGame::generate_killer_with_rng(block_size(), &mut rng).unwrap();
+            let text = issue.setting().cages_to_string();
+            let setting = GameSetting::new(block_size()).load_cages(&text).unwrap();
+            assert_eq!(setting.cages(), issue.setting().cages());
+            let mut game = NormalGame::new(setting);
+            game.load(&issue.to_string());
+            assert!(game.solve_with_dlx().unwrap() == solved);
+        }
+    }
+    mod generate_killer_with_budget {
+        use super::*;
+        use std::time::Duration;
+        use std::time::Instant;
+        fn block_size() -> BlockSize {
+            BlockSize {
+                height: 3,
+                width: 3,
+            }
+        }
+        #[test]
+        fn it_generates_the_same_game_as_generate_killer_with_rng() {
+            let budget = Budget::new().timeout(Duration::from_secs(60));
+            let (issue1, solved1) = NormalGame::generate_killer_with_budget_and_rng(
+                block_size(),
+                &budget,
+                &mut StdRng::seed_from_u64(0),
+            )
+            .unwrap()
+            .unwrap();
+            let (issue2, solved2) =
+                NormalGame::generate_killer_with_rng(block_size(), &mut StdRng::seed_from_u64(0))
+                    .unwrap();
+            assert_eq!(issue1.to_string(), issue2.to_string());
+            assert_eq!(
+                issue1.setting().cages_to_string(),
+                issue2.setting().cages_to_string()
+            );
+            assert!(solved1 == solved2);
+        }
+        #[test]
+        fn it_gives_up_at_the_deadline() {
+            let result = NormalGame::generate_killer_with_budget(
+                BlockSize {
+                    height: 4,
+                    width: 4,
+                },
+                &Budget::new().deadline(Instant::now()),
+            );
+            assert!(result.is_err());
+        }
+    }
+    mod generate_with_budget {
+        use super::*;
+        use std::sync::atomic::AtomicBool;
+        use std::sync::Arc;
+        use std::time::Duration;
+        use std::time::Instant;
+        #[test]
+        fn it_generates_the_same_game_as_generate_with_seed() {
+            let block_size = BlockSize {
+                height: 3,
+                width: 3,
+            };
+            let budget = Budget::new().timeout(Duration::from_secs(60));
+            let (issue1, solved1) = NormalGame::generate_with_budget_and_rng(
+                block_size,
+                &budget,
+                &mut ChaCha8Rng::seed_from_u64(3),
+            )
+            .unwrap();
+            let (issue2, solved2) = NormalGame::generate_with_seed(block_size, 3);
+            assert_eq!(issue1.to_string(), issue2.to_string());
+            assert_eq!(solved1.to_string(), solved2.to_string());
+        }
+        #[test]
+        fn it_generates_the_size_without_pattern() {
+            let (issue, solved) = NormalGame::generate_with_budget(
+                BlockSize {
+                    height: 1,
+                    width: 4,
+                },
+                &Budget::new().timeout(Duration::from_secs(60)),
+            )
+            .unwrap();
+            assert_eq!(issue.solve().unwrap().to_string(), solved.to_string());
+        }
+        #[test]
+        fn it_gives_up_at_the_deadline() {
+            let result = NormalGame::generate_with_budget(
+                BlockSize {
+                    height: 5,
+                    width: 5,
+                },
+                &Budget::new().deadline(Instant::now()),
+            );
+            assert!(result.is_err());
+        }
+        #[test]
+        fn it_gives_up_when_cancelled() {
+            let result = NormalGame::generate_with_budget(
+                BlockSize {
+                    height: 3,
+                    width: 5,
+                },
+                &Budget::new().cancel(Arc::new(AtomicBool::new(true))),
+            );
+            assert!(result.is_err());
+        }
+    }
+    mod generate_with_setting_and_budget {
+        use super::*;
+        use crate::normal_game::relation::PeerRelation;
+        use std::time::Duration;
+        use std::time::Instant;
+        #[test]
+        fn it_generates_the_same_game_as_generate_with_setting() {
+            let setting = GameSetting::new(BlockSize {
+                height: 3,
+                width: 3,
+            })
+            .with_diagonals();
+            let budget = Budget::new().timeout(Duration::from_secs(60));
+            let (issue1, solved1) = NormalGame::generate_with_setting_and_budget_and_rng(
+                &setting,
+                &budget,
+                &mut StdRng::seed_from_u64(3),
+            )
+            .unwrap()
+            .unwrap();
+            let (issue2, solved2) =
+                NormalGame::generate_with_setting_and_rng(&setting, &mut StdRng::seed_from_u64(3))
+                    .unwrap();
+            assert_eq!(issue1.to_string(), issue2.to_string());
+            assert_eq!(solved1.to_string(), solved2.to_string());
+            assert!(issue1.setting().has_diagonals());
+        }
+        #[test]
+        fn it_gives_up_at_the_deadline() {
+            let setting = GameSetting::new(BlockSize {
+                height: 4,
+                width: 4,
+            })
+            .with_diagonals();
+            let result = NormalGame::generate_with_setting_and_budget(
+                &setting,
+                &Budget::new().deadline(Instant::now()),
+            );
+            assert!(result.is_err());
+        }
+        #[test]
+        fn it_returns_none_for_the_setting_without_solution() {
+            let setting = GameSetting::new(BlockSize {
+                height: 2,
+                width: 2,
+            })
+            .with_peer_relation(PeerRelation::AntiKing);
+            let result = NormalGame::generate_with_setting_and_budget(&setting, &Budget::new());
+            assert!(matches!(result, Ok(None)));
+        }
+    }
+    mod generate_with_difficulty {
+        use super::*;
+        fn generate(difficulty: Difficulty, seed: u64) -> Option<(NormalGame, NormalGame)> {
+            NormalGame::generate_with_difficulty_and_rng(
+                BlockSize {
+                    height: 3,
+                    width: 3,
+                },
+                difficulty,
+                &mut StdRng::seed_from_u64(seed),
+                20,
+            )
+        }
+        #[test]
+        fn it_generates_easy_issue() {
+            let (issue, solved) = generate(Difficulty::Easy, 1).unwrap();
+            assert_eq!(issue.difficulty(), Difficulty::Easy);
+            assert!(issue.has_unique_solution());
+            assert_eq!(issue.solve().unwrap().to_string(), solved.to_string());
+        }
+        #[test]
+        fn it_generates_medium_issue() {
+            let (issue, solved) = generate(Difficulty::Medium, 1).unwrap();
+            assert_eq!(issue.difficulty(), Difficulty::Medium);
+            assert_eq!(issue.solve().unwrap().to_string(), solved.to_string());
+        }
+        #[test]
+        fn it_generates_expert_issue() {
+            let (issue, solved) = generate(Difficulty::Expert, 1).unwrap();
+            assert_eq!(issue.difficulty(), Difficulty::Expert);
+            assert!(issue.has_unique_solution());
+            assert_eq!(issue.solve().unwrap().to_string(), solved.to_string());
+        }
+        #[test]
+        fn it_generates_hard_issue() {
+            let (issue, solved) = generate(Difficulty::Hard, 6).unwrap();
+            assert_eq!(issue.difficulty(), Difficulty::Hard);
+            assert!(issue.has_unique_solution());
+            assert_eq!(issue.solve().unwrap().to_string(), solved.to_string());
+        }
+    }
     mod to_issue_9x9 {
         use super::*;
         #[test]
@@ -136,7 +901,7 @@ mod tests {
                 width: 3,
             }));
             game.load("174392865|682715943|935468721|528176439|417839652|369254187|893541276|746923518|251687394");
-            let game = NormalGame::to_issue(&game);
+            let game = NormalGame::to_issue(&game, &mut thread_rng());
             println!("{}", game.to_string_with_newline());
             assert!(false);
         }
@@ -151,7 +916,7 @@ mod tests {
                 width: 4,
             }));
             game.load("1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16|5,6,7,8,9,10,11,12,13,14,15,16,1,2,3,4|9,10,11,12,13,14,15,16,1,2,3,4,5,6,7,8|13,14,15,16,1,2,3,4,5,6,7,8,9,10,11,12|2,3,4,1,6,7,8,5,10,11,12,9,14,15,16,13|6,7,8,5,10,11,12,9,14,15,16,13,2,3,4,1|10,11,12,9,14,15,16,13,2,3,4,1,6,7,8,5|14,15,16,13,2,3,4,1,6,7,8,5,10,11,12,9|3,4,1,2,7,8,5,6,11,12,9,10,15,16,13,14|7,8,5,6,11,12,9,10,15,16,13,14,3,4,1,2|11,12,9,10,15,16,13,14,3,4,1,2,7,8,5,6|15,16,13,14,3,4,1,2,7,8,5,6,11,12,9,10|4,1,2,3,8,5,6,7,12,9,10,11,16,13,14,15|8,5,6,7,12,9,10,11,16,13,14,15,4,1,2,3|12,9,10,11,16,13,14,15,4,1,2,3,8,5,6,7|16,13,14,15,4,1,2,3,8,5,6,7,12,9,10,11");
-            let game = NormalGame::to_issue(&game);
+            let game = NormalGame::to_issue(&game, &mut thread_rng());
             println!("{}", game.to_string_with_newline());
             assert!(false);
         }
@@ -1,5 +1,12 @@
+use crate::normal_game::budget::Budget;
+use crate::normal_game::budget::GaveUp;
+use crate::normal_game::budget::SolveOutcome;
+use crate::normal_game::difficulty::Difficulty;
+use crate::normal_game::load::LoadError;
+use crate::normal_game::rating::Rating;
 use crate::normal_game::setting::BlockSize;
 use crate::normal_game::setting::GameSetting;
+use crate::normal_game::strategy::SolveStrategy;
 use crate::normal_game::NormalGame;
 
 mod generator;
@@ -17,14 +24,115 @@ mod pattern;
 ///
 /// 1x3, 2x2, 2x3, 3x2, 4x2, 2x4, 3x3, 2x5, 5x2, 4x3, 3x4, 4x4, 4x5, 5x4, 5x5
 ///
-/// Even though it is quick, it will take a few seconds for 3x4 and 4x3 sizes,
-/// about 30 seconds for 4x5 and 5x4 sizes, and about a minute for 5x5 sizes.
-/// 素早くと言っても、3x4,4x3のサイズで数秒、4x5,5x4のサイズで約30秒、5x5のサイズで1分程度の時間はかかると思われる。
+/// Even though it is quick, in a release build it takes about 10 milliseconds for 3x4 and 4x3 sizes,
+/// up to half a second for 4x5 and 5x4 sizes, and up to a second for 5x5 sizes.
+/// 素早くと言っても、リリースビルドで 3x4,4x3 のサイズで約 10 ミリ秒、4x5,5x4 のサイズで最大 0.5 秒、5x5 のサイズで最大 1 秒程度の時間はかかる。
+///
+/// NormalGame is `Send + Sync`, so it can be called from worker threads and the result can be passed to other threads.
+/// NormalGame は `Send + Sync` であるため、ワーカースレッドから呼び出し、結果を他のスレッドに渡すことができる。
 ///
 pub fn generate_numberplace(block_size: BlockSize) -> (NormalGame, NormalGame) {
     NormalGame::generate(block_size)
 }
 
+/// Generate a number-place problem from the seed.
+/// The same block_size and seed always yield the same question and answer,
+/// so a problem can be regenerated from its block_size and seed.
+/// The random number generator is ChaCha8, whose output does not depend on the platform or the version of rand.
+///
+/// シードからナンバープレースの問題を生成する。
+/// block_size とシードが同じであれば常に同じ問題と答えが生成されるため、block_size とシードから問題を再生成できる。
+/// 乱数生成器は ChaCha8 であり、その出力はプラットフォームや rand のバージョンに依存しない。
+///
+pub fn generate_numberplace_with_seed(
+    block_size: BlockSize,
+    seed: u64,
+) -> (NormalGame, NormalGame) {
+    NormalGame::generate_with_seed(block_size, seed)
+}
+
+/// Generate a number-place problem of the setting, which may be a variant such as Sudoku X.
+/// For example, `GameSetting::new(block_size).with_diagonals()` generates a problem of Sudoku X.
+///
+/// 指定した設定のナンバープレースの問題を生成する。設定は対角線ナンプレなどのバリアントでもよい。
+/// 例えば、`GameSetting::new(block_size).with_diagonals()` を指定すると対角線ナンプレの問題を生成する。
+///
+/// Returns None if the setting has no solution, for example Anti-King on a 4x4 board.
+/// 4x4 の盤面のアンチキングなど、設定に解答が存在しない場合は None を返却する。
+///
+pub fn generate_numberplace_with_setting(
+    setting: &GameSetting,
+) -> Option<(NormalGame, NormalGame)> {
+    NormalGame::generate_with_setting(setting)
+}
+
+/// Generate a Killer Sudoku problem, which has few or no answers given.
+/// The cages can be saved with `issue.setting().cages_to_string()` and loaded with `GameSetting::load_cages`.
+///
+/// キラーナンプレの問題を生成する。問題の解答は少ないか、全くない。
+/// ケージは `issue.setting().cages_to_string()` で保存し、`GameSetting::load_cages` で読み込むことができる。
+///
+/// Returns None if no solved game of the block size can be made.
+/// ブロックサイズの解答済みのゲームを作成できない場合は None を返却する。
+///
+pub fn generate_killer_numberplace(block_size: BlockSize) -> Option<(NormalGame, NormalGame)> {
+    NormalGame::generate_killer(block_size)
+}
+
+/// Generate a Killer Sudoku problem within the budget.
+/// Returns Err(GaveUp) if the deadline has passed, the cancel flag has been set or too many guesses are required.
+///
+/// 上限の範囲でキラーナンプレの問題を生成する。
+/// 時刻を過ぎた場合、キャンセルのフラグが設定された場合、または推測が多すぎる場合は Err(GaveUp) を返却する。
+///
+pub fn generate_killer_numberplace_with_budget(
+    block_size: BlockSize,
+    budget: &Budget,
+) -> Result<Option<(NormalGame, NormalGame)>, GaveUp> {
+    NormalGame::generate_killer_with_budget(block_size, budget)
+}
+
+/// Generate a number-place problem of the specified difficulty.
+/// Returns None if a problem of the difficulty could not be generated within the budget.
+///
+/// 指定した難易度のナンバープレースの問題を生成する。
+/// 上限回数以内に指定した難易度の問題を生成できなかった場合は None を返却する。
+///
+pub fn generate_numberplace_with_difficulty(
+    block_size: BlockSize,
+    difficulty: Difficulty,
+) -> Option<(NormalGame, NormalGame)> {
+    NormalGame::generate_with_difficulty(block_size, difficulty)
+}
+
+/// Generate a number-place problem within the budget.
+/// Returns Err(GaveUp) if the deadline has passed, the cancel flag has been set or too many guesses are required.
+///
+/// 上限の範囲でナンバープレースの問題を生成する。
+/// 時刻を過ぎた場合、キャンセルのフラグが設定された場合、または推測が多すぎる場合は Err(GaveUp) を返却する。
+///
+pub fn generate_numberplace_with_budget(
+    block_size: BlockSize,
+    budget: &Budget,
+) -> Result<(NormalGame, NormalGame), GaveUp> {
+    NormalGame::generate_with_budget(block_size, budget)
+}
+
+/// Generate a number-place problem of the setting, which may be a variant such as Sudoku X, within the budget.
+/// Returns Ok(None) if the setting has no solution,
+/// and Err(GaveUp) if the deadline has passed, the cancel flag has been set or too many guesses are required.
+///
+/// 上限の範囲で、指定した設定のナンバープレースの問題を生成する。設定は対角線ナンプレなどのバリアントでもよい。
+/// 設定に解答が存在しない場合は Ok(None) を、
+/// 時刻を過ぎた場合、キャンセルのフラグが設定された場合、または推測が多すぎる場合は Err(GaveUp) を返却する。
+///
+pub fn generate_numberplace_with_setting_and_budget(
+    setting: &GameSetting,
+    budget: &Budget,
+) -> Result<Option<(NormalGame, NormalGame)>, GaveUp> {
+    NormalGame::generate_with_setting_and_budget(setting, budget)
+}
+
 /// Solve number-place issues.
 /// The second argument, issue, can be a string such as the following.  
 ///
@@ -40,6 +148,72 @@ pub fn solve_numberplace(block_size: BlockSize, issue: &str) -> Option<NormalGam
     game.solve()
 }
 
+/// Solve number-place issues with the strategy.
+/// The second argument, issue, can be a string in the same format as `solve_numberplace`.
+/// For example, `SolveStrategy::Logical` can be used for grading and `SolveStrategy::Backtracking` for validation.
+///
+/// 指定した戦略でナンバープレースの問題を解く。
+/// 第二引数の issue は `solve_numberplace` と同じ形式の文字列を指定可能である。
+/// 例えば、難易度の判定には `SolveStrategy::Logical` を、検証には `SolveStrategy::Backtracking` を使用できる。
+///
+pub fn solve_numberplace_with_strategy(
+    block_size: BlockSize,
+    issue: &str,
+    strategy: SolveStrategy,
+) -> Option<NormalGame> {
+    let mut game = NormalGame::new(GameSetting::new(block_size));
+    game.load(issue);
+    game.solve_with(strategy)
+}
+
+/// Solve number-place issues with the strategy within the budget.
+/// Returns Ok(SolveOutcome::NoSolution) if the issue is inconsistent,
+/// Ok(SolveOutcome::Stalled) if the strategy is Logical and the techniques are not enough,
+/// and Err(GaveUp) if the budget has run out.
+///
+/// 指定した戦略で、上限の範囲でナンバープレースの問題を解く。
+/// 問題に矛盾がある場合は Ok(SolveOutcome::NoSolution) を、戦略が Logical でテクニックで解けない場合は
+/// Ok(SolveOutcome::Stalled) を、上限に達した場合は Err(GaveUp) を返却する。
+///
+pub fn solve_numberplace_with_budget(
+    block_size: BlockSize,
+    issue: &str,
+    strategy: SolveStrategy,
+    budget: &Budget,
+) -> Result<SolveOutcome, GaveUp> {
+    let mut game = NormalGame::new(GameSetting::new(block_size));
+    game.load(issue);
+    game.solve_with_budget(strategy, budget)
+}
+
+/// Verify that a number-place issue has exactly one answer.
+/// The second argument, issue, can be a string in the same format as `solve_numberplace`,
+/// but each row must have as many cells as the side size, as with `NormalGame::try_load`.
+/// A malformed issue is rejected with Err, and a contradictory issue returns Ok(false) because it has no answer.
+///
+/// ナンバープレースの問題の解答がただ 1 つであることを検証する。
+/// 第二引数の issue は `solve_numberplace` と同じ形式の文字列を指定可能であるが、
+/// `NormalGame::try_load` と同様に各行は一辺のサイズと同じ数のセルを持つ必要がある。
+/// 不正な形式の問題は Err で拒否し、矛盾のある問題は解答を持たないため Ok(false) を返却する。
+///
+pub fn verify_numberplace(block_size: BlockSize, issue: &str) -> Result<bool, LoadError> {
+    let mut game = NormalGame::new(GameSetting::new(block_size));
+    game.try_load(issue)?;
+    Ok(game.has_unique_solution())
+}
+
+/// Rate a number-place issue by solving it with the techniques as a human would.
+/// The second argument, issue, can be a string in the same format as `solve_numberplace`.
+///
+/// 人間と同じようにテクニックでナンバープレースの問題を解き、レーティングする。
+/// 第二引数の issue は `solve_numberplace` と同じ形式の文字列を指定可能である。
+///
+pub fn rate_numberplace(block_size: BlockSize, issue: &str) -> Rating {
+    let mut game = NormalGame::new(GameSetting::new(block_size));
+    game.load(issue);
+    game.rate()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -73,4 +247,150 @@ mod tests {
             assert!(false);
         }
     }
+    mod solve_with_strategy {
+        use super::*;
+        #[test]
+        fn it_solves_with_each_strategy() {
+            let block_size = BlockSize {
+                height: 3,
+                width: 3,
+            };
+            let issue =
+                "5 2 9 1|   1   8|3    6  2| 4    7|6       1|  5    9|9  7    4| 6   3|  7 2 5 3";
+            let solved = solve_numberplace(block_size, issue);
+            for strategy in [
+                SolveStrategy::Backtracking,
+                SolveStrategy::Logical,
+                SolveStrategy::DancingLinks,
+            ]
+            .iter()
+            {
+                assert!(solve_numberplace_with_strategy(block_size, issue, *strategy) == solved);
+            }
+        }
+    }
+    mod solve_with_budget {
+        use super::*;
+        fn block_size() -> BlockSize {
+            BlockSize {
+                height: 3,
+                width: 3,
+            }
+        }
+        #[test]
+        fn it_distinguishes_giving_up_from_inconsistent_issue() {
+            let budget = Budget::new().max_guesses(0);
+            let hard = "4     8 5| 3       |   7     |  2     6 |    8 4  |    1    |   6 3 7 |5  2     |1 4      ";
+            assert_eq!(
+                solve_numberplace_with_budget(
+                    block_size(),
+                    hard,
+                    SolveStrategy::Backtracking,
+                    &budget
+                )
+                .map(|s| s.solution().is_some()),
+                Err(GaveUp)
+            );
+            assert!(matches!(
+                solve_numberplace_with_budget(
+                    block_size(),
+                    "11",
+                    SolveStrategy::Backtracking,
+                    &budget
+                ),
+                Ok(SolveOutcome::NoSolution)
+            ));
+        }
+    }
+    mod thread_safety {
+        use super::*;
+        use crate::normal_game::cell::Cell;
+        use crate::normal_game::group::Group;
+        use std::thread;
+        fn assert_send_sync<T: Send + Sync>() {}
+        #[test]
+        fn game_types_are_send_and_sync() {
+            assert_send_sync::<NormalGame>();
+            assert_send_sync::<GameSetting>();
+            assert_send_sync::<Group>();
+            assert_send_sync::<Cell>();
+        }
+        #[test]
+        fn it_can_generate_and_solve_in_threads() {
+            let block_size = BlockSize {
+                height: 2,
+                width: 3,
+            };
+            let handles: Vec<thread::JoinHandle<(NormalGame, NormalGame)>> = (0..4)
+                .map(|_| thread::spawn(move || generate_numberplace(block_size)))
+                .collect();
+            let generated: Vec<(NormalGame, NormalGame)> =
+                handles.into_iter().map(|h| h.join().unwrap()).collect();
+            let handles: Vec<thread::JoinHandle<bool>> = generated
+                .into_iter()
+                .map(|(issue, solved)| {
+                    thread::spawn(move || {
+                        solve_numberplace(block_size, &issue.to_string_with_comma()) == Some(solved)
+                    })
+                })
+                .collect();
+            assert!(handles.into_iter().all(|h| h.join().unwrap()));
+        }
+    }
+    mod verify {
+        use super::*;
+        fn block_size() -> BlockSize {
+            BlockSize {
+                height: 3,
+                width: 3,
+            }
+        }
+        #[test]
+        fn it_returns_true_for_unique_issue() {
+            assert_eq!(
+                verify_numberplace(block_size(), "5 2 9 1  |   1   8 |3    6  2| 4    7  |6       1|  5    9 |9  7    4| 6   3   |  7 2 5 3"),
+                Ok(true)
+            );
+        }
+        #[test]
+        fn it_returns_false_for_multiple_answers() {
+            assert_eq!(
+                verify_numberplace(block_size(), "  2 9 1  |   1   8 |3    6  2| 4    7  |6       1|  5    9 |9  7    4| 6   3   |  7 2 5 3"),
+                Ok(false)
+            );
+        }
+        #[test]
+        fn it_returns_false_for_contradictory_issue() {
+            assert_eq!(
+                verify_numberplace(block_size(), "55 9 11  |   1   8 |3    6  2| 4    7  |6       1|  5    9 |9  7    4| 6   3   |  7 2 5 3"),
+                Ok(false)
+            );
+        }
+        #[test]
+        fn it_rejects_malformed_issue() {
+            assert!(matches!(
+                verify_numberplace(block_size(), "5 x 9 1  |   1   8 |3    6  2| 4    7  |6       1|  5    9 |9  7    4| 6   3   |  7 2 5 3"),
+                Err(LoadError::InvalidToken { .. })
+            ));
+            assert!(matches!(
+                verify_numberplace(block_size(), "5 2 9 1|   1   8"),
+                Err(LoadError::WrongRowCount { .. })
+            ));
+        }
+    }
+    mod rate {
+        use super::*;
+        #[test]
+        fn it_rates_the_issue() {
+            let rating = rate_numberplace(
+                BlockSize {
+                    height: 3,
+                    width: 3,
+                },
+                "5 2 9 1|   1   8|3    6  2| 4    7|6       1|  5    9|9  7    4| 6   3|  7 2 5 3",
+            );
+            assert!(rating.score > 0.0);
+            assert!(rating.steps > 0);
+        }
+    }
 }
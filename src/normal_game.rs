@@ -1,83 +1,215 @@
-use std::cell::RefCell;
-use std::rc::Rc;
+use std::fmt;
+use std::sync::Arc;
 
+pub mod budget;
+pub mod cage;
+pub mod candidates;
 pub mod cell;
+pub mod difficulty;
+pub mod dlx;
 pub mod group;
+pub mod hint;
+pub mod load;
+pub mod rating;
+pub mod relation;
 pub mod remove_answer;
 pub mod setting;
 pub mod shuffle;
 pub mod solve;
+pub mod strategy;
+pub mod technique;
+pub mod trace;
 
+/// The cells are held in a flat array indexed by `y * side_size + x`.
+/// The groups hold the indexes of their cells, and the indexes of the groups and the peers of each cell are precomputed.
+/// Since the precomputed tables are shared, cloning a game only copies the answers and the answer_candidate.
+///
+/// セルは `y * side_size + x` をインデックスとするフラットな配列で保持する。
+/// グループはセルのインデックスを保持し、各セルが所属するグループとピアのインデックスは事前に計算しておく。
+/// 事前に計算したテーブルは共有されるため、ゲームの複製では解答と answer_candidate のみがコピーされる。
+#[derive(Clone)]
 pub struct NormalGame {
     setting: setting::GameSetting,
-    cells: Vec<Rc<RefCell<cell::Cell>>>,
-    groups: Vec<Rc<RefCell<group::Group>>>,
+    cells: Vec<cell::Cell>,
+    groups: Vec<group::Group>,
+    cell_groups: Arc<Vec<Vec<usize>>>,
+    peers: Arc<Vec<Vec<usize>>>,
+    cell_cages: Arc<Vec<Vec<usize>>>,
     answered_count: u32,
 }
 
 impl NormalGame {
     pub fn new(setting: setting::GameSetting) -> NormalGame {
         let cells = cell::create_cells(&setting);
-        let groups = group::create_groups(&cells, &setting);
-        NormalGame {
+        let groups = group::create_groups(&setting);
+        let cell_groups = group::create_cell_groups(&groups, cells.len());
+        let mut peers = group::create_peers(&groups, &cell_groups);
+        cage::add_cage_peers(&mut peers, setting.cages(), setting.side_size());
+        relation::add_relation_peers(&mut peers, setting.peer_relations(), setting.side_size());
+        let cell_cages = cage::create_cell_cages(setting.cages(), setting.side_size());
+        let mut game = NormalGame {
             setting,
             cells,
             groups,
+            cell_groups: Arc::new(cell_groups),
+            peers: Arc::new(peers),
+            cell_cages: Arc::new(cell_cages),
             answered_count: 0,
+        };
+        for c in 0..game.setting.cages().len() {
+            game.restrict_cage(c);
         }
+        game
     }
 
     pub fn setting(&self) -> &setting::GameSetting {
         &self.setting
     }
-    pub fn cells(&self) -> &Vec<Rc<RefCell<cell::Cell>>> {
+    pub fn cells(&self) -> &Vec<cell::Cell> {
         &self.cells
     }
-    pub fn groups(&self) -> &Vec<Rc<RefCell<group::Group>>> {
+    pub fn groups(&self) -> &Vec<group::Group> {
         &self.groups
     }
     pub fn answered_count(&self) -> u32 {
         self.answered_count
     }
-    /// ' 7     6 |6   1   3|  54 87  |  8   4  | 1  3  5 |  9   1  |  35 12  |7   2   8| 5     9 '
-    pub fn load(&mut self, issue: &str) {
-        let answer_columns: Vec<&str> = issue.split("|").collect();
-        for (y, horizontal_line) in answer_columns.iter().enumerate() {
-            let horizontal_line = horizontal_line.to_string();
-            let answers: Vec<String> = if horizontal_line.contains(',') {
-                horizontal_line.split(',').map(|s| s.to_string()).collect()
-            } else {
-                horizontal_line.chars().map(|c| format!("{}", c)).collect()
-            };
-            for (x, answer) in answers.iter().enumerate() {
-                if *answer == " " || *answer == "" {
-                    continue;
-                }
-                let answer: u8 = answer.parse().expect("issue is wrong.");
-                self.set_answer(cell::Position::new(x as u8, y as u8), answer);
-            }
+    /// Returns the indexes of the groups that the cell at the index belongs to.
+    ///
+    /// 指定したインデックスのセルが所属するグループのインデックスを返却する。
+    pub fn groups_of(&self, index: usize) -> &[usize] {
+        &self.cell_groups[index]
+    }
+    /// Returns the indexes of the other cells that cannot have the same answer as the cell at the index,
+    /// which are the cells sharing a group or a cage with it, and the cells in the peer relations.
+    ///
+    /// 指定したインデックスのセルと同じ解答を持てない他のセルのインデックスを返却する。
+    /// グループまたはケージを共有するセルと、ピアの関係にあるセルである。
+    pub fn peers_of(&self, index: usize) -> &[usize] {
+        &self.peers[index]
+    }
+    /// Returns the index of the cell at the position, or None if the position is outside the game.
+    ///
+    /// 指定した Position のセルのインデックスを返却する。ゲームの範囲外の場合は None を返却する。
+    pub fn index_of(&self, pos: cell::Position) -> Option<usize> {
+        let side_size = self.setting.side_size();
+        if pos.x() < side_size && pos.y() < side_size {
+            Some(pos.index(side_size))
+        } else {
+            None
         }
     }
-
     pub fn set_answer(&mut self, pos: cell::Position, answer: u8) {
-        let cell = self.find_cell(pos).unwrap();
-        if cell.borrow().answer().is_some() {
+        let index = self.index_of(pos).unwrap();
+        if self.cells[index].answer().is_some() {
             return;
         }
-        cell.borrow_mut().set_answer(answer);
-        self.groups()
-            .iter()
-            .filter(|g| g.borrow().cells().iter().any(|c| c.borrow().pos() == pos))
-            .for_each(|g| g.borrow_mut().remove_answer_candidate(answer));
+        self.cells[index].set_answer(answer);
+        for g in self.cell_groups[index].iter() {
+            self.groups[*g].remove_answer_candidate(answer);
+        }
+        for p in self.peers[index].iter() {
+            self.cells[*p].remove_answer_candidate(answer);
+        }
+        let cell_cages = Arc::clone(&self.cell_cages);
+        for c in cell_cages[index].iter() {
+            self.restrict_cage(*c);
+        }
         self.answered_count += 1;
     }
 
+    /// Remove the answer_candidate that cannot add up to the sum of the cage from its unanswered cells.
+    ///
+    /// ケージの合計を満たせない answer_candidate を、ケージ内の解答のないセルから除去する。
+    fn restrict_cage(&mut self, cage: usize) {
+        let restricted = self.setting.cages()[cage].restrict(&self.cells, self.setting.side_size());
+        for (index, answer_candidate) in restricted {
+            self.cells[index].restore_answer_candidate(answer_candidate);
+        }
+    }
+
+    /// Set the answer like `set_answer`, but refuse it if the position is outside the game,
+    /// the answer is not in the answer_candidate of the setting, the cell already has an answer,
+    /// or the same answer already exists in the peers of the cell.
+    /// If refused because of the peers, it returns the positions of all the conflicting cells.
+    ///
+    /// `set_answer` と同様に解答を設定するが、Position がゲームの範囲外の場合、解答が設定の answer_candidate にない場合、
+    /// セルに既に解答がある場合、またはセルのピアに同じ解答が既に存在する場合は拒否する。
+    /// ピアが原因で拒否した場合は、衝突している全てのセルの Position を返却する。
+    pub fn try_set_answer(
+        &mut self,
+        pos: cell::Position,
+        answer: u8,
+    ) -> Result<(), SetAnswerError> {
+        let index = self
+            .index_of(pos)
+            .ok_or(SetAnswerError::OutOfRange { pos })?;
+        if !self.setting.candidates().contains(answer) {
+            return Err(SetAnswerError::InvalidAnswer { pos, answer });
+        }
+        if let Some(current) = self.cells[index].answer() {
+            return Err(SetAnswerError::AlreadyAnswered {
+                pos,
+                answer: current,
+            });
+        }
+        let conflicts = self.conflicting_peers(index, answer);
+        if !conflicts.is_empty() {
+            return Err(SetAnswerError::Conflict(conflicts));
+        }
+        self.set_answer(pos, answer);
+        Ok(())
+    }
+
+    /// Returns the positions of the other cells that have the specified answer among the peers of the cell,
+    /// which are the cells sharing a group or a cage with it, and the cells in the peer relations.
+    /// It returns Err if the position is outside the game.
+    ///
+    /// 指定したセルのピアのうち、指定した解答を持つ他のセルの Position を返却する。
+    /// ピアはグループまたはケージを共有するセルと、ピアの関係にあるセルである。
+    /// Position がゲームの範囲外の場合は Err を返却する。
+    pub fn find_conflicting_peers(
+        &self,
+        pos: cell::Position,
+        answer: u8,
+    ) -> Result<Vec<cell::Position>, SetAnswerError> {
+        let index = self
+            .index_of(pos)
+            .ok_or(SetAnswerError::OutOfRange { pos })?;
+        Ok(self.conflicting_peers(index, answer))
+    }
+
+    fn conflicting_peers(&self, index: usize, answer: u8) -> Vec<cell::Position> {
+        self.peers[index]
+            .iter()
+            .map(|p| &self.cells[*p])
+            .filter(|c| c.answer() == Some(answer))
+            .map(|c| c.pos())
+            .collect()
+    }
+
+    /// Remove the answer_candidate from the cell without setting an answer.
+    /// The cages of the cell are restricted again, because the other cells may no longer add up to the sum.
+    ///
+    /// 解答を設定せずに、セルから answer_candidate を除去する。
+    /// 他のセルが合計を満たせなくなる可能性があるため、セルのケージを再度制限する。
+    pub fn remove_answer_candidate(&mut self, pos: cell::Position, candidate: u8) {
+        let index = self.index_of(pos).unwrap();
+        if !self.cells[index].has_answer_candidate(candidate) {
+            return;
+        }
+        self.cells[index].remove_answer_candidate(candidate);
+        let cell_cages = Arc::clone(&self.cell_cages);
+        for c in cell_cages[index].iter() {
+            self.restrict_cage(*c);
+        }
+    }
+
     pub fn check_status(&mut self) -> GameState {
         if self
             .cells()
             .iter()
-            .filter(|c| c.borrow().answer() == None)
-            .any(|c| c.borrow().answer_candidate_count() == 0)
+            .any(|c| c.answer().is_none() && c.answer_candidate_count() == 0)
         {
             return GameState::Failure;
         }
@@ -85,92 +217,70 @@ impl NormalGame {
         if self
             .groups()
             .iter()
-            .any(|g| g.borrow().is_duplicate_answer())
+            .any(|g| g.is_duplicate_answer(&self.cells))
         {
             return GameState::Failure;
         }
 
-        if (self.setting.side_size() as u32 * self.setting.side_size() as u32)
-            == self.answered_count
+        let side_size = self.setting.side_size();
+        if self
+            .setting
+            .cages()
+            .iter()
+            .any(|c| c.is_violated(&self.cells, side_size))
         {
+            return GameState::Failure;
+        }
+
+        if relation::is_violated(self.setting.peer_relations(), &self.cells, side_size) {
+            return GameState::Failure;
+        }
+
+        if self.cells.len() as u32 == self.answered_count {
             return if self.is_all_clear_groups_answer_candidate() {
                 GameState::Complete
             } else {
                 GameState::Failure
             };
         }
-        return GameState::Solving;
+        GameState::Solving
     }
 
     fn is_all_clear_groups_answer_candidate(&self) -> bool {
         self.groups()
             .iter()
-            .all(|g| g.borrow().is_all_clear_answer_candidate())
+            .all(|g| g.is_all_clear_answer_candidate())
     }
 
-    pub fn find_cell(&self, pos: cell::Position) -> Option<&Rc<RefCell<cell::Cell>>> {
-        self.cells.iter().find(|c| c.borrow().pos() == pos)
+    pub fn find_cell(&self, pos: cell::Position) -> Option<&cell::Cell> {
+        self.index_of(pos).map(|index| &self.cells[index])
     }
 
     pub fn to_string(&self) -> String {
-        let mut str = String::new();
-        for y in 0..self.setting.side_size() {
-            for x in 0..self.setting.side_size() {
-                let str2 = match self
-                    .find_cell(cell::Position::new(x, y))
-                    .unwrap()
-                    .borrow()
-                    .answer()
-                {
-                    Some(a) => a.to_string(),
-                    None => " ".to_string(),
-                };
-                str = format!("{}{}", str, str2);
-            }
-            str = format!("{}{}", str, '|');
-        }
-        str.pop();
-        return str;
+        self.to_string_with_separator("", '|')
     }
     pub fn to_string_with_comma(&self) -> String {
-        let mut str = String::new();
-        for y in 0..self.setting.side_size() {
-            for x in 0..self.setting.side_size() {
-                let str2 = match self
-                    .find_cell(cell::Position::new(x, y))
-                    .unwrap()
-                    .borrow()
-                    .answer()
-                {
-                    Some(a) => a.to_string(),
-                    None => " ".to_string(),
-                };
-                str = format!("{}{}{}", str, if x == 0 { "" } else { "," }, str2);
-            }
-            str = format!("{}{}", str, '|');
-        }
-        str.pop();
-        return str;
+        self.to_string_with_separator(",", '|')
     }
     pub fn to_string_with_newline(&self) -> String {
+        self.to_string_with_separator(",", '\n')
+    }
+    fn to_string_with_separator(&self, separator: &str, line_separator: char) -> String {
         let mut str = String::new();
-        for y in 0..self.setting.side_size() {
-            for x in 0..self.setting.side_size() {
-                let str2 = match self
-                    .find_cell(cell::Position::new(x, y))
-                    .unwrap()
-                    .borrow()
-                    .answer()
-                {
-                    Some(a) => a.to_string(),
-                    None => " ".to_string(),
-                };
-                str = format!("{}{}{}", str, if x == 0 { "" } else { "," }, str2);
+        for (i, cell) in self.cells.iter().enumerate() {
+            if i != 0 {
+                if cell.pos().x() == 0 {
+                    str.push(line_separator);
+                } else {
+                    str.push_str(separator);
+                }
+            }
+            match cell.answer() {
+                Some(a) => str.push_str(&a.to_string()),
+                None => str.push(' '),
             }
-            str = format!("{}{}", str, '\n');
         }
-        str.pop();
-        return str;
+        str
     }
 }
 
@@ -181,17 +291,54 @@ impl PartialEq for NormalGame {
     }
 }
 
-impl Clone for NormalGame {
-    fn clone(&self) -> Self {
-        let mut new_game = NormalGame::new(self.setting.clone());
-        self.cells()
-            .iter()
-            .filter(|c| c.borrow().answer() != None)
-            .for_each(|c| new_game.set_answer(c.borrow().pos(), c.borrow().answer().unwrap()));
-        new_game
+/// The reason why `try_set_answer` refused the answer.
+///
+/// `try_set_answer` が解答を拒否した理由。
+#[derive(Debug, PartialEq, Clone)]
+pub enum SetAnswerError {
+    /// The position is outside the game.
+    OutOfRange { pos: cell::Position },
+    /// The answer is not in the answer_candidate of the setting.
+    InvalidAnswer { pos: cell::Position, answer: u8 },
+    /// The cell already has an answer, which is not replaced.
+    AlreadyAnswered { pos: cell::Position, answer: u8 },
+    /// The peers of the cell at these positions already have the same answer.
+    Conflict(Vec<cell::Position>),
+}
+
+impl fmt::Display for SetAnswerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetAnswerError::OutOfRange { pos } => {
+                write!(f, "row {}, column {} is out of the board", pos.y(), pos.x())
+            }
+            SetAnswerError::InvalidAnswer { pos, answer } => write!(
+                f,
+                "answer {} at row {}, column {} is not in the answer_candidate",
+                answer,
+                pos.y(),
+                pos.x()
+            ),
+            SetAnswerError::AlreadyAnswered { pos, answer } => write!(
+                f,
+                "row {}, column {} already has answer {}",
+                pos.y(),
+                pos.x(),
+                answer
+            ),
+            SetAnswerError::Conflict(peers) => {
+                write!(f, "the same answer is at")?;
+                for peer in peers {
+                    write!(f, " (row {}, column {})", peer.y(), peer.x())?;
+                }
+                Ok(())
+            }
+        }
     }
 }
 
+impl std::error::Error for SetAnswerError {}
+
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum GameState {
     Solving,
@@ -257,6 +404,212 @@ mod tests {
             assert_eq!(game.groups.len(), 27);
         }
     }
+    mod diagonals {
+        use super::*;
+        fn setting() -> GameSetting {
+            GameSetting::new(BlockSize {
+                height: 3,
+                width: 3,
+            })
+            .with_diagonals()
+        }
+        #[test]
+        fn it_has_29_groups() {
+            let game = NormalGame::new(setting());
+            assert_eq!(game.groups.len(), 27 + 2);
+        }
+        #[test]
+        fn it_detects_duplicate_answers_on_the_diagonal() {
+            let issue = "1||||||||        1";
+            let mut game = NormalGame::new(setting::GameSetting::new(BlockSize {
+                height: 3,
+                width: 3,
+            }));
+            game.load(issue);
+            assert_eq!(game.check_status(), GameState::Solving);
+            let mut game = NormalGame::new(setting());
+            game.load(issue);
+            assert_eq!(game.check_status(), GameState::Failure);
+        }
+        #[test]
+        fn it_removes_the_answer_candidate_on_the_diagonal() {
+            let mut game = NormalGame::new(setting());
+            game.set_answer(cell::Position::new(0, 0), 2);
+            assert!(!game.cells()[80].has_answer_candidate(2));
+            assert!(game.cells()[9 + 7].has_answer_candidate(2));
+        }
+    }
+    mod cages {
+        use super::*;
+        fn game() -> NormalGame {
+            NormalGame::new(
+                GameSetting::new(BlockSize {
+                    height: 3,
+                    width: 3,
+                })
+                .load_cages("3:0,0 1,0|23:0,1 0,2 1,2")
+                .unwrap(),
+            )
+        }
+        fn answer_candidate(game: &NormalGame, x: u8, y: u8) -> Vec<u8> {
+            game.find_cell(cell::Position::new(x, y))
+                .unwrap()
+                .candidates()
+                .iter()
+                .collect()
+        }
+        #[test]
+        fn it_restricts_the_answer_candidate_by_the_sums() {
+            let game = game();
+            assert_eq!(answer_candidate(&game, 0, 0), vec![1, 2]);
+            assert_eq!(answer_candidate(&game, 1, 0), vec![1, 2]);
+            // 23 = 6 + 8 + 9, and (0, 1) and (0, 2) are in the same column as (0, 0).
+            assert_eq!(answer_candidate(&game, 0, 1), vec![6, 8, 9]);
+            assert_eq!(answer_candidate(&game, 1, 2), vec![6, 8, 9]);
+            assert!(game.peers_of(0).contains(&1));
+            assert!(game.peers_of(9).contains(&19));
+        }
+        #[test]
+        fn it_restricts_and_restores_the_answer_candidate_of_the_cage() {
+            let mut game = game();
+            game.set_answer(cell::Position::new(0, 1), 9);
+            assert_eq!(answer_candidate(&game, 0, 2), vec![6, 8]);
+            assert_eq!(answer_candidate(&game, 1, 2), vec![6, 8]);
+            game.set_answer(cell::Position::new(0, 2), 8);
+            assert_eq!(answer_candidate(&game, 1, 2), vec![6]);
+            game.remove_answer(cell::Position::new(0, 1));
+            assert_eq!(answer_candidate(&game, 0, 1), vec![6, 9]);
+            assert_eq!(answer_candidate(&game, 1, 2), vec![6, 9]);
+            game.remove_answer(cell::Position::new(0, 2));
+            assert_eq!(answer_candidate(&game, 1, 2), vec![6, 8, 9]);
+        }
+        #[test]
+        fn it_restricts_the_cage_after_removing_the_answer_candidate() {
+            let mut game = NormalGame::new(
+                GameSetting::new(BlockSize {
+                    height: 3,
+                    width: 3,
+                })
+                .load_cages("5:4,4 5,4")
+                .unwrap(),
+            );
+            assert_eq!(answer_candidate(&game, 5, 4), vec![1, 2, 3, 4]);
+            // 5 = 1 + 4 or 2 + 3, so only 1 + 4 is left when (4, 4) cannot be 2 or 3.
+            game.remove_answer_candidate(cell::Position::new(4, 4), 2);
+            game.remove_answer_candidate(cell::Position::new(4, 4), 3);
+            assert_eq!(answer_candidate(&game, 5, 4), vec![1, 4]);
+        }
+        #[test]
+        fn it_detects_the_wrong_sum() {
+            let mut game = game();
+            game.set_answer(cell::Position::new(0, 0), 1);
+            assert_eq!(game.check_status(), GameState::Solving);
+            game.set_answer(cell::Position::new(1, 0), 3);
+            assert_eq!(game.check_status(), GameState::Failure);
+        }
+    }
+    mod peer_relations {
+        use super::*;
+        use crate::normal_game::relation::PeerRelation;
+        use crate::normal_game::strategy::SolveStrategy;
+        fn setting(relation: PeerRelation) -> GameSetting {
+            GameSetting::new(BlockSize {
+                height: 3,
+                width: 3,
+            })
+            .with_peer_relation(relation)
+        }
+        const ANTI_KNIGHT: &str = "         | 1   5   |   3  4  |   97    |  1      | 6   3   |6    7   |2      4 |    5  2 ";
+        const ANTI_KNIGHT_SOLUTION: &str = "536749182|412685739|879312465|385974216|921568374|764123958|648237591|257891643|193456827";
+        const ANTI_KING: &str = "  5     7| 9 1     |  7 9 3  |6       1|  9   28 |8   4    | 3    4  |5  3 7   |   5    8";
+        const ANTI_KING_SOLUTION: &str = "425863917|396175842|187492356|643258791|759631284|812749563|238916475|564387129|971524638";
+        #[test]
+        fn it_removes_the_answer_candidate_a_knight_step_away() {
+            let mut game = NormalGame::new(setting(PeerRelation::AntiKnight));
+            game.set_answer(cell::Position::new(4, 4), 5);
+            assert!(!game.cells()[2 * 9 + 3].has_answer_candidate(5));
+            assert!(!game.cells()[5 * 9 + 6].has_answer_candidate(5));
+            assert!(game.cells()[5 * 9 + 7].has_answer_candidate(5));
+            game.remove_answer(cell::Position::new(4, 4));
+            assert!(game.cells()[2 * 9 + 3].has_answer_candidate(5));
+        }
+        #[test]
+        fn it_removes_the_answer_candidate_a_king_step_away() {
+            let mut game = NormalGame::new(setting(PeerRelation::AntiKing));
+            game.set_answer(cell::Position::new(2, 2), 5);
+            assert!(!game.cells()[3 * 9 + 3].has_answer_candidate(5));
+            assert!(game.cells()[4 * 9 + 4].has_answer_candidate(5));
+        }
+        #[test]
+        fn it_detects_duplicate_answers_a_knight_step_away() {
+            let issue = "  1||   1";
+            let mut game = NormalGame::new(GameSetting::new(BlockSize {
+                height: 3,
+                width: 3,
+            }));
+            game.load(issue);
+            assert_eq!(game.check_status(), GameState::Solving);
+            let mut game = NormalGame::new(setting(PeerRelation::AntiKnight));
+            game.load(issue);
+            assert_eq!(game.check_status(), GameState::Failure);
+            assert_eq!(
+                game.find_conflicting_peers(cell::Position::new(2, 0), 1),
+                Ok(vec![cell::Position::new(3, 2)])
+            );
+        }
+        #[test]
+        fn it_accepts_the_grid_without_equal_answers_a_knight_or_king_step_away() {
+            // Each row is the previous one shifted by three, and by one more at each band.
+            let grid = "123456789|456789123|789123456|234567891|567891234|891234567|345678912|678912345|912345678";
+            let setting =
+                setting(PeerRelation::AntiKnight).with_peer_relation(PeerRelation::AntiKing);
+            let mut game = NormalGame::new(setting);
+            game.load(grid);
+            assert_eq!(game.check_status(), GameState::Complete);
+        }
+        #[test]
+        fn it_solves_anti_knight_puzzle() {
+            let mut plain = NormalGame::new(GameSetting::new(BlockSize {
+                height: 3,
+                width: 3,
+            }));
+            plain.load(ANTI_KNIGHT);
+            assert!(plain.count_solutions_with_dlx(2) > 1);
+            let mut game = NormalGame::new(setting(PeerRelation::AntiKnight));
+            game.load(ANTI_KNIGHT);
+            assert!(game.has_unique_solution());
+            for strategy in [
+                SolveStrategy::Backtracking,
+                SolveStrategy::Logical,
+                SolveStrategy::DancingLinks,
+            ]
+            .iter()
+            {
+                assert_eq!(
+                    game.solve_with(*strategy).unwrap().to_string(),
+                    ANTI_KNIGHT_SOLUTION
+                );
+            }
+        }
+        #[test]
+        fn it_solves_anti_king_puzzle() {
+            let mut game = NormalGame::new(setting(PeerRelation::AntiKing));
+            game.load(ANTI_KING);
+            assert_eq!(game.count_solutions_with_dlx(2), 1);
+            for strategy in [
+                SolveStrategy::Backtracking,
+                SolveStrategy::Logical,
+                SolveStrategy::DancingLinks,
+            ]
+            .iter()
+            {
+                assert_eq!(
+                    game.solve_with(*strategy).unwrap().to_string(),
+                    ANTI_KING_SOLUTION
+                );
+            }
+        }
+    }
     mod test_load {
         use super::*;
         const GAME_STRING:&str = " 7     6 |6   1   3|  54 87  |  8   4  | 1  3  5 |  9   1  |  35 12  |7   2   8| 5     9 ";
@@ -281,10 +634,9 @@ mod tests {
                     game()
                         .cells
                         .iter()
-                        .find(|c| c.borrow().pos() == cell::Position::new(0, 0))
+                        .find(|c| c.pos() == cell::Position::new(0, 0))
                         // .find_by_position(&cell::Position::new(0, 0))
                         .unwrap()
-                        .borrow()
                         .answer(),
                     None
                 );
@@ -295,10 +647,9 @@ mod tests {
                     game()
                         .cells
                         .iter()
-                        .find(|c| c.borrow().pos() == cell::Position::new(1, 0))
+                        .find(|c| c.pos() == cell::Position::new(1, 0))
                         // .find_by_position(&cell::Position::new(1, 0))
                         .unwrap()
-                        .borrow()
                         .answer(),
                     Some(7)
                 );
@@ -309,10 +660,9 @@ mod tests {
                     game()
                         .cells
                         .iter()
-                        .find(|c| c.borrow().pos() == cell::Position::new(2, 0))
+                        .find(|c| c.pos() == cell::Position::new(2, 0))
                         // .find_by_position(&cell::Position::new(2, 0))
                         .unwrap()
-                        .borrow()
                         .answer(),
                     None
                 );
@@ -323,10 +673,9 @@ mod tests {
                     game()
                         .cells
                         .iter()
-                        .find(|c| c.borrow().pos() == cell::Position::new(7, 0))
+                        .find(|c| c.pos() == cell::Position::new(7, 0))
                         // .find_by_position(&cell::Position::new(7, 0))
                         .unwrap()
-                        .borrow()
                         .answer(),
                     Some(6)
                 );
@@ -365,13 +714,82 @@ mod tests {
         fn test() {
             let mut game = NormalGame::new(setting());
             game.set_answer(cell::Position::new(0, 0), 2);
-            assert_eq!(game.cells()[0].borrow().answer(), Some(2));
+            assert_eq!(game.cells()[0].answer(), Some(2));
         }
         #[test]
         fn remove_unanswerd_candidate_from_groups() {
             let mut game = NormalGame::new(setting());
             game.set_answer(cell::Position::new(0, 0), 2);
-            assert_eq!(game.cells()[1].borrow().has_answer_candidate(2), false);
+            assert_eq!(game.cells()[1].has_answer_candidate(2), false);
+        }
+    }
+    mod try_set_answer {
+        use super::*;
+        #[test]
+        fn it_sets_answer_without_conflict() {
+            let mut game = NormalGame::new(setting());
+            game.set_answer(cell::Position::new(0, 0), 2);
+            assert_eq!(game.try_set_answer(cell::Position::new(1, 0), 3), Ok(()));
+            assert_eq!(game.cells()[1].answer(), Some(3));
+        }
+        #[test]
+        fn it_returns_all_conflicting_peers() {
+            let mut game = NormalGame::new(setting());
+            game.set_answer(cell::Position::new(0, 0), 2);
+            game.set_answer(cell::Position::new(4, 1), 2);
+            game.set_answer(cell::Position::new(1, 5), 2);
+            assert_eq!(
+                game.try_set_answer(cell::Position::new(1, 1), 2),
+                Err(SetAnswerError::Conflict(vec![
+                    cell::Position::new(4, 1),
+                    cell::Position::new(1, 5),
+                    cell::Position::new(0, 0),
+                ]))
+            );
+            assert_eq!(
+                game.find_cell(cell::Position::new(1, 1)).unwrap().answer(),
+                None
+            );
+        }
+        #[test]
+        fn it_refuses_the_answered_cell() {
+            let mut game = NormalGame::new(setting());
+            game.set_answer(cell::Position::new(0, 0), 2);
+            assert_eq!(
+                game.try_set_answer(cell::Position::new(0, 0), 3),
+                Err(SetAnswerError::AlreadyAnswered {
+                    pos: cell::Position::new(0, 0),
+                    answer: 2
+                })
+            );
+            assert_eq!(game.cells()[0].answer(), Some(2));
+        }
+        #[test]
+        fn it_refuses_the_position_outside_the_game() {
+            let mut game = NormalGame::new(setting());
+            let pos = cell::Position::new(6, 0);
+            assert_eq!(
+                game.try_set_answer(pos, 1),
+                Err(SetAnswerError::OutOfRange { pos })
+            );
+            assert_eq!(
+                game.find_conflicting_peers(pos, 1),
+                Err(SetAnswerError::OutOfRange { pos })
+            );
+            assert_eq!(game.answered_count(), 0);
+        }
+        #[test]
+        fn it_refuses_the_answer_outside_the_answer_candidate() {
+            let mut game = NormalGame::new(setting());
+            let pos = cell::Position::new(0, 0);
+            for answer in [0, 7].iter().copied() {
+                assert_eq!(
+                    game.try_set_answer(pos, answer),
+                    Err(SetAnswerError::InvalidAnswer { pos, answer })
+                );
+            }
+            assert_eq!(game.cells()[0].answer(), None);
+            assert_eq!(game.answered_count(), 0);
         }
     }
 }
@@ -0,0 +1,311 @@
+use crate::normal_game::strategy::SolveStrategy;
+use crate::normal_game::GameState;
+use crate::normal_game::NormalGame;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+/// The limits of solving or generating a game.
+/// The default has no limits.
+///
+/// - deadline: The time to give up.
+/// - max_guesses: The number of guesses to give up after. The answers assumed while backtracking are counted.
+/// - cancel: The flag to give up. It can be set from another thread.
+///
+/// ゲームを解く、または生成する際の上限。
+/// デフォルトでは上限はない。
+///
+/// - deadline: 諦める時刻。
+/// - max_guesses: 諦めるまでの推測の回数。バックトラックの際に仮定した解答の数を数える。
+/// - cancel: 諦めるためのフラグ。他のスレッドから設定することができる。
+#[derive(Debug, Clone, Default)]
+pub struct Budget {
+    pub deadline: Option<Instant>,
+    pub max_guesses: Option<usize>,
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl Budget {
+    pub fn new() -> Budget {
+        Budget::default()
+    }
+
+    /// Give up at the deadline.
+    ///
+    /// 指定した時刻に諦める。
+    pub fn deadline(mut self, deadline: Instant) -> Budget {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Give up when the timeout has elapsed from now.
+    ///
+    /// 現在から指定した時間が経過した時点で諦める。
+    pub fn timeout(self, timeout: Duration) -> Budget {
+        self.deadline(Instant::now() + timeout)
+    }
+
+    /// Give up when more than `max_guesses` guesses are required.
+    ///
+    /// `max_guesses` 回を超える推測が必要になった時点で諦める。
+    pub fn max_guesses(mut self, max_guesses: usize) -> Budget {
+        self.max_guesses = Some(max_guesses);
+        self
+    }
+
+    /// Give up when the flag is set to true.
+    ///
+    /// フラグが true に設定された時点で諦める。
+    pub fn cancel(mut self, cancel: Arc<AtomicBool>) -> Budget {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Returns Err if the budget has run out after `guesses` guesses.
+    ///
+    /// `guesses` 回の推測の後に上限に達している場合は Err を返却する。
+    // `Option::is_some_and` needs Rust 1.70, which is newer than the toolchains this crate supports.
+    #[allow(clippy::unnecessary_map_or)]
+    pub(crate) fn check(&self, guesses: usize) -> Result<(), GaveUp> {
+        if self.max_guesses.map_or(false, |max| guesses > max) {
+            return Err(GaveUp);
+        }
+        if self.deadline.map_or(false, |d| Instant::now() >= d) {
+            return Err(GaveUp);
+        }
+        if let Some(cancel) = &self.cancel {
+            if cancel.load(Ordering::Relaxed) {
+                return Err(GaveUp);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The error returned when the budget has run out before the game is solved or generated.
+/// It is distinct from None, which means that the game is inconsistent.
+///
+/// ゲームを解く、または生成する前に上限に達した場合に返却するエラー。
+/// ゲームに矛盾があることを意味する None とは区別される。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GaveUp;
+
+/// The result of solving a game within the budget.
+///
+/// - Solved: The game is solved.
+/// - NoSolution: The game is inconsistent and has no solution.
+/// - Stalled: The strategy is Logical and the techniques are not enough.
+///   The game may still have a solution, which can be found by guessing.
+///
+/// 上限の範囲でゲームを解いた結果。
+///
+/// - Solved: ゲームを解いた。
+/// - NoSolution: ゲームに矛盾があり、解答がない。
+/// - Stalled: 戦略が Logical で、テクニックで解けない。
+///   仮定を行えば解答が見つかる可能性はある。
+#[derive(Clone, PartialEq)]
+pub enum SolveOutcome {
+    Solved(NormalGame),
+    NoSolution,
+    Stalled,
+}
+
+impl SolveOutcome {
+    /// Returns the solved game, or None if the game is not solved.
+    ///
+    /// 解いたゲームを返却する。ゲームを解けなかった場合は None を返却する。
+    pub fn solution(self) -> Option<NormalGame> {
+        match self {
+            SolveOutcome::Solved(game) => Some(game),
+            _ => None,
+        }
+    }
+}
+
+impl From<Option<NormalGame>> for SolveOutcome {
+    fn from(solution: Option<NormalGame>) -> Self {
+        match solution {
+            Some(game) => SolveOutcome::Solved(game),
+            None => SolveOutcome::NoSolution,
+        }
+    }
+}
+
+impl NormalGame {
+    /// Solve the game with the strategy within the budget.
+    /// It returns Ok with the outcome, or Err(GaveUp) if the budget has run out.
+    /// The solved game is the same as the result of `solve_with`.
+    /// Only the logical strategy returns Stalled, when the techniques are not enough.
+    /// The logical strategy does not guess, so only the deadline and the cancel flag are checked between the steps.
+    ///
+    /// 指定した戦略で上限の範囲でゲームを解く。
+    /// 結果を Ok で返却し、上限に達した場合は Err(GaveUp) を返却する。
+    /// 解いたゲームは `solve_with` の結果と同じである。
+    /// Stalled を返却するのは Logical のみで、テクニックで解けない場合である。
+    /// Logical は推測を行わないため、手順の間に時刻とキャンセルのフラグのみを確認する。
+    pub fn solve_with_budget(
+        &self,
+        strategy: SolveStrategy,
+        budget: &Budget,
+    ) -> Result<SolveOutcome, GaveUp> {
+        match strategy {
+            SolveStrategy::Backtracking => {
+                self.solve_by_backtracking_within(budget).map(From::from)
+            }
+            SolveStrategy::Logical => self.solve_logically_within(budget),
+            SolveStrategy::DancingLinks => self.solve_with_dlx_within(budget).map(From::from),
+        }
+    }
+
+    /// Search in the same order as `solutions`, so the result is the same as `solve`.
+    fn solve_by_backtracking_within(&self, budget: &Budget) -> Result<Option<NormalGame>, GaveUp> {
+        let mut guesses = 0;
+        let mut stack: Vec<(NormalGame, bool)> = vec![(self.clone(), false)];
+        while let Some((mut game, guessed)) = stack.pop() {
+            if guessed {
+                guesses += 1;
+            }
+            budget.check(guesses)?;
+            match game.fill_lonely_and_eliminate() {
+                GameState::Complete => return Ok(Some(game)),
+                GameState::Failure => continue,
+                GameState::Solving => {}
+            }
+            let cell = match game
+                .cells()
+                .iter()
+                .filter(|c| c.answer_candidate_count() != 0)
+                .min_by_key(|c| c.answer_candidate_count())
+            {
+                Some(cell) => cell,
+                None => continue,
+            };
+            let pos = cell.pos();
+            let candidates = NormalGame::ordered_answer_candidate(&game, cell.candidates());
+            for candidate in candidates.into_iter().rev() {
+                let mut new_game = game.clone();
+                new_game.set_answer(pos, candidate);
+                stack.push((new_game, true));
+            }
+        }
+        Ok(None)
+    }
+
+    fn solve_logically_within(&self, budget: &Budget) -> Result<SolveOutcome, GaveUp> {
+        let techniques = SolveStrategy::logical_techniques();
+        let mut game = self.clone();
+        loop {
+            budget.check(0)?;
+            match game.check_status() {
+                GameState::Complete => return Ok(SolveOutcome::Solved(game)),
+                GameState::Failure => return Ok(SolveOutcome::NoSolution),
+                GameState::Solving => {}
+            }
+            match game.find_step(&techniques) {
+                Some(step) => game.apply_step(&step),
+                None => return Ok(SolveOutcome::Stalled),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normal_game::setting::BlockSize;
+    use crate::normal_game::setting::GameSetting;
+    fn game(issue: &str) -> NormalGame {
+        let mut game = NormalGame::new(GameSetting::new(BlockSize {
+            height: 3,
+            width: 3,
+        }));
+        game.load(issue);
+        game
+    }
+    const STRATEGIES: [SolveStrategy; 3] = [
+        SolveStrategy::Backtracking,
+        SolveStrategy::Logical,
+        SolveStrategy::DancingLinks,
+    ];
+    const HARD: &str = "4     8 5| 3       |   7     |  2     6 |    8 4  |    1    |   6 3 7 |5  2     |1 4      ";
+    mod solve_with_budget {
+        use super::*;
+        #[test]
+        fn it_returns_the_same_result_without_limits() {
+            let game = game(HARD);
+            for strategy in STRATEGIES.iter() {
+                let solved = game.solve_with_budget(*strategy, &Budget::new()).unwrap();
+                assert!(solved.solution() == game.solve_with(*strategy));
+            }
+        }
+        #[test]
+        fn it_distinguishes_inconsistent_game() {
+            let game = game("11");
+            for strategy in STRATEGIES.iter() {
+                assert!(matches!(
+                    game.solve_with_budget(*strategy, &Budget::new().max_guesses(0)),
+                    Ok(SolveOutcome::NoSolution)
+                ));
+            }
+        }
+        #[test]
+        fn it_distinguishes_stalled_techniques_from_inconsistent_game() {
+            assert!(matches!(
+                game("").solve_with_budget(SolveStrategy::Logical, &Budget::new()),
+                Ok(SolveOutcome::Stalled)
+            ));
+            assert!(matches!(
+                game("").solve_with_budget(SolveStrategy::Backtracking, &Budget::new()),
+                Ok(SolveOutcome::Solved(_))
+            ));
+        }
+        #[test]
+        fn it_gives_up_after_max_guesses() {
+            let game = game(HARD);
+            let budget = Budget::new().max_guesses(3);
+            assert_eq!(
+                game.solve_with_budget(SolveStrategy::Backtracking, &budget)
+                    .map(|s| s.solution().is_some()),
+                Err(GaveUp)
+            );
+            assert_eq!(
+                game.solve_with_budget(SolveStrategy::DancingLinks, &budget)
+                    .map(|s| s.solution().is_some()),
+                Err(GaveUp)
+            );
+        }
+        #[test]
+        fn it_gives_up_at_the_deadline() {
+            let game = game(HARD);
+            let budget = Budget::new().deadline(Instant::now());
+            for strategy in STRATEGIES.iter() {
+                assert_eq!(
+                    game.solve_with_budget(*strategy, &budget)
+                        .map(|s| s.solution().is_some()),
+                    Err(GaveUp)
+                );
+            }
+        }
+        #[test]
+        fn it_gives_up_when_cancelled() {
+            let game = game(HARD);
+            let cancel = Arc::new(AtomicBool::new(false));
+            let budget = Budget::new()
+                .timeout(Duration::from_secs(60))
+                .cancel(cancel.clone());
+            assert!(game
+                .solve_with_budget(SolveStrategy::Backtracking, &budget)
+                .is_ok());
+            cancel.store(true, Ordering::Relaxed);
+            for strategy in STRATEGIES.iter() {
+                assert_eq!(
+                    game.solve_with_budget(*strategy, &budget)
+                        .map(|s| s.solution().is_some()),
+                    Err(GaveUp)
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,447 @@
+use crate::normal_game::candidates::Candidates;
+use crate::normal_game::cell::Cell;
+use crate::normal_game::cell::Position;
+use std::fmt;
+use std::sync::Arc;
+
+/// A cage of Killer Sudoku.
+/// The answers of the cells in a cage are all different and their sum is `sum`.
+/// Unlike a group, a cage does not have to hold every answer_candidate.
+///
+/// キラーナンプレのケージ。
+/// ケージ内のセルの解答は全て異なり、その合計は `sum` である。
+/// グループと異なり、ケージは全ての answer_candidate を保有する必要はない。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cage {
+    sum: u32,
+    cells: Arc<[Position]>,
+}
+
+impl Cage {
+    pub fn new(sum: u32, cells: Vec<Position>) -> Cage {
+        Cage {
+            sum,
+            cells: cells.into(),
+        }
+    }
+
+    pub fn sum(&self) -> u32 {
+        self.sum
+    }
+
+    pub fn cells(&self) -> &[Position] {
+        &self.cells
+    }
+
+    /// Returns the combinations of `count` different digits in `digits` whose sum is `sum`.
+    ///
+    /// `digits` に含まれる異なる `count` 個の数字のうち、合計が `sum` となる組み合わせを返却する。
+    pub fn sum_combinations(digits: Candidates, count: usize, sum: u32) -> Vec<Candidates> {
+        let digits: Vec<u8> = digits.iter().collect();
+        let mut combinations: Vec<Candidates> = vec![];
+        collect_sum_combinations(&digits, count, sum, Candidates::new(), &mut combinations);
+        combinations
+    }
+
+    /// Returns the narrowed answer_candidate of the unanswered cells in the cage.
+    /// An answer_candidate is kept only if it is in a sum combination of the rest of the cage
+    /// that every unanswered cell can take part in.
+    ///
+    /// ケージ内の解答のないセルについて、絞り込んだ answer_candidate を返却する。
+    /// answer_candidate は、解答のない全てのセルが含まれうるケージの残りの合計の組み合わせに含まれる場合のみ残す。
+    pub(crate) fn restrict(&self, cells: &[Cell], side_size: u8) -> Vec<(usize, Candidates)> {
+        let indexes: Vec<usize> = self.cells.iter().map(|p| p.index(side_size)).collect();
+        let answers: Vec<u8> = indexes.iter().filter_map(|i| cells[*i].answer()).collect();
+        let unanswered: Vec<usize> = indexes
+            .iter()
+            .copied()
+            .filter(|i| cells[*i].answer().is_none())
+            .collect();
+        let answered_sum: u32 = answers.iter().map(|a| *a as u32).sum();
+        let used: Candidates = answers.iter().collect();
+        let available = unanswered
+            .iter()
+            .fold(Candidates::new(), |acc, i| {
+                acc.union(cells[*i].candidates())
+            })
+            .difference(used);
+        let allowed = if answered_sum > self.sum {
+            Candidates::new()
+        } else {
+            Self::sum_combinations(available, unanswered.len(), self.sum - answered_sum)
+                .into_iter()
+                .filter(|c| {
+                    unanswered
+                        .iter()
+                        .all(|i| !cells[*i].candidates().intersection(*c).is_empty())
+                })
+                .fold(Candidates::new(), |acc, c| acc.union(c))
+        };
+        unanswered
+            .iter()
+            .map(|i| (*i, cells[*i].candidates().intersection(allowed)))
+            .collect()
+    }
+
+    /// Returns true if the answers in the cage are duplicated, exceed the sum,
+    /// or do not add up to the sum when all the cells are answered.
+    ///
+    /// ケージ内の解答が重複している場合、合計を超えている場合、
+    /// または全てのセルに解答があるにもかかわらず合計と一致しない場合に true を返す。
+    pub fn is_violated(&self, cells: &[Cell], side_size: u8) -> bool {
+        let answers: Vec<u8> = self
+            .cells
+            .iter()
+            .filter_map(|p| cells[p.index(side_size)].answer())
+            .collect();
+        let used: Candidates = answers.iter().collect();
+        let sum: u32 = answers.iter().map(|a| *a as u32).sum();
+        used.len() != answers.len()
+            || sum > self.sum
+            || (answers.len() == self.cells.len() && sum != self.sum)
+    }
+}
+
+fn collect_sum_combinations(
+    digits: &[u8],
+    count: usize,
+    sum: u32,
+    current: Candidates,
+    combinations: &mut Vec<Candidates>,
+) {
+    if count == 0 {
+        if sum == 0 {
+            combinations.push(current);
+        }
+        return;
+    }
+    for (i, digit) in digits.iter().enumerate() {
+        if *digit as u32 > sum {
+            break;
+        }
+        let mut next = current;
+        next.insert(*digit);
+        collect_sum_combinations(
+            &digits[i + 1..],
+            count - 1,
+            sum - *digit as u32,
+            next,
+            combinations,
+        );
+    }
+}
+
+/// Returns the indexes of the cages that each cell belongs to.
+pub fn create_cell_cages(cages: &[Cage], side_size: u8) -> Vec<Vec<usize>> {
+    let cell_count = side_size as usize * side_size as usize;
+    let mut cell_cages: Vec<Vec<usize>> = vec![vec![]; cell_count];
+    for (c, cage) in cages.iter().enumerate() {
+        for pos in cage.cells().iter() {
+            cell_cages[pos.index(side_size)].push(c);
+        }
+    }
+    cell_cages
+}
+
+/// Add the other cells in the same cage to the peers, because their answers are all different.
+///
+/// ケージ内の解答は全て異なるため、同じケージの他のセルをピアに追加する。
+pub fn add_cage_peers(peers: &mut [Vec<usize>], cages: &[Cage], side_size: u8) {
+    for cage in cages.iter() {
+        let indexes: Vec<usize> = cage.cells().iter().map(|p| p.index(side_size)).collect();
+        for i in indexes.iter() {
+            for j in indexes.iter() {
+                if i != j && !peers[*i].contains(j) {
+                    peers[*i].push(*j);
+                }
+            }
+        }
+    }
+}
+
+/// The reason why cage definitions are invalid.
+///
+/// ケージの定義が不正である理由。
+#[derive(Debug, PartialEq, Clone)]
+pub enum CageError {
+    /// The definition of a cage is not in the form of `sum:x,y x,y`.
+    InvalidFormat { text: String },
+    /// The position is outside of the board.
+    OutOfRange { pos: Position },
+    /// The cell belongs to more than one cage.
+    Overlap { pos: Position },
+    /// The cage has no cells.
+    Empty { cage: usize },
+    /// No combination of different answer_candidate adds up to the sum of the cage.
+    ImpossibleSum { cage: usize, sum: u32 },
+}
+
+impl fmt::Display for CageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CageError::InvalidFormat { text } => write!(f, "invalid cage {:?}", text),
+            CageError::OutOfRange { pos } => {
+                write!(f, "row {}, column {} is out of the board", pos.y(), pos.x())
+            }
+            CageError::Overlap { pos } => write!(
+                f,
+                "row {}, column {} belongs to more than one cage",
+                pos.y(),
+                pos.x()
+            ),
+            CageError::Empty { cage } => write!(f, "cage {} has no cells", cage),
+            CageError::ImpossibleSum { cage, sum } => {
+                write!(f, "the sum {} of cage {} is impossible", sum, cage)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CageError {}
+
+/// Parse the cage definitions.
+/// The cages are separated by `|`, and each cage is the sum followed by the positions of the cells,
+/// such as `3:0,0 1,0|15:2,0 2,1 3,1`.
+///
+/// ケージの定義を読み込む。
+/// ケージは `|` で区切り、各ケージは合計とそれに続くセルの位置で表す。例えば `3:0,0 1,0|15:2,0 2,1 3,1` のように指定する。
+pub fn parse_cages(text: &str) -> Result<Vec<Cage>, CageError> {
+    text.split('|')
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty())
+        .map(|t| {
+            let invalid = || CageError::InvalidFormat {
+                text: t.to_string(),
+            };
+            let (sum, cells) = t.split_once(':').ok_or_else(invalid)?;
+            let sum: u32 = sum.trim().parse().map_err(|_| invalid())?;
+            let cells = cells
+                .split_whitespace()
+                .map(|cell| {
+                    let (x, y) = cell.split_once(',').ok_or_else(invalid)?;
+                    let x: u8 = x.parse().map_err(|_| invalid())?;
+                    let y: u8 = y.parse().map_err(|_| invalid())?;
+                    Ok(Position::new(x, y))
+                })
+                .collect::<Result<Vec<Position>, CageError>>()?;
+            Ok(Cage::new(sum, cells))
+        })
+        .collect()
+}
+
+/// Returns the cage definitions in the format of `parse_cages`.
+///
+/// `parse_cages` の形式でケージの定義を返却する。
+pub fn cages_to_string(cages: &[Cage]) -> String {
+    cages
+        .iter()
+        .map(|cage| {
+            let cells: Vec<String> = cage
+                .cells()
+                .iter()
+                .map(|p| format!("{},{}", p.x(), p.y()))
+                .collect();
+            format!("{}:{}", cage.sum(), cells.join(" "))
+        })
+        .collect::<Vec<String>>()
+        .join("|")
+}
+
+/// Returns Err if a cage is empty, out of the board, overlaps another cage or has an impossible sum.
+pub(crate) fn validate_cages(
+    cages: &[Cage],
+    side_size: u8,
+    candidates: Candidates,
+) -> Result<(), CageError> {
+    let mut used: Vec<Position> = vec![];
+    for (c, cage) in cages.iter().enumerate() {
+        if cage.cells().is_empty() {
+            return Err(CageError::Empty { cage: c });
+        }
+        for pos in cage.cells().iter() {
+            if pos.x() >= side_size || pos.y() >= side_size {
+                return Err(CageError::OutOfRange { pos: *pos });
+            }
+            if used.contains(pos) {
+                return Err(CageError::Overlap { pos: *pos });
+            }
+            used.push(*pos);
+        }
+        if Cage::sum_combinations(candidates, cage.cells().len(), cage.sum()).is_empty() {
+            return Err(CageError::ImpossibleSum {
+                cage: c,
+                sum: cage.sum(),
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normal_game::cell::create_cells;
+    use crate::normal_game::setting::BlockSize;
+    use crate::normal_game::setting::GameSetting;
+    fn digits() -> Candidates {
+        (1..=9).collect()
+    }
+    fn cells() -> Vec<Cell> {
+        create_cells(&GameSetting::new(BlockSize {
+            height: 3,
+            width: 3,
+        }))
+    }
+    fn to_vec(candidates: &[Candidates]) -> Vec<Vec<u8>> {
+        candidates.iter().map(|c| c.iter().collect()).collect()
+    }
+    mod sum_combinations {
+        use super::*;
+        #[test]
+        fn it_returns_the_combinations_of_the_sum() {
+            assert_eq!(
+                to_vec(&Cage::sum_combinations(digits(), 2, 10)),
+                vec![vec![1, 9], vec![2, 8], vec![3, 7], vec![4, 6]]
+            );
+            assert_eq!(
+                to_vec(&Cage::sum_combinations(digits(), 3, 6)),
+                vec![vec![1, 2, 3]]
+            );
+            assert_eq!(
+                to_vec(&Cage::sum_combinations(digits(), 9, 45)),
+                vec![(1..=9).collect::<Vec<u8>>()]
+            );
+        }
+        #[test]
+        fn it_returns_nothing_for_impossible_sum() {
+            assert!(Cage::sum_combinations(digits(), 2, 2).is_empty());
+            assert!(Cage::sum_combinations(digits(), 2, 18).is_empty());
+        }
+    }
+    mod restrict {
+        use super::*;
+        #[test]
+        fn it_keeps_the_digits_of_the_combinations() {
+            let cage = Cage::new(4, vec![Position::new(0, 0), Position::new(1, 0)]);
+            let restricted = cage.restrict(&cells(), 9);
+            assert_eq!(restricted.len(), 2);
+            assert_eq!(restricted[0].1.iter().collect::<Vec<u8>>(), vec![1, 3]);
+        }
+        #[test]
+        fn it_considers_the_answers_and_the_candidates() {
+            let cage = Cage::new(
+                15,
+                vec![
+                    Position::new(0, 0),
+                    Position::new(1, 0),
+                    Position::new(2, 0),
+                ],
+            );
+            let mut cells = cells();
+            cells[0].set_answer(9);
+            // The rest is 6 = 1 + 5 = 2 + 4, but (2, 0) cannot hold 1 or 5.
+            cells[2].remove_answer_candidate(1);
+            cells[2].remove_answer_candidate(5);
+            let restricted = cage.restrict(&cells, 9);
+            assert_eq!(restricted[0].0, 1);
+            assert_eq!(restricted[0].1.iter().collect::<Vec<u8>>(), vec![2, 4]);
+            assert_eq!(restricted[1].1.iter().collect::<Vec<u8>>(), vec![2, 4]);
+        }
+    }
+    mod is_violated {
+        use super::*;
+        fn cage() -> Cage {
+            Cage::new(5, vec![Position::new(0, 0), Position::new(1, 0)])
+        }
+        #[test]
+        fn it_accepts_the_sum() {
+            let mut cells = cells();
+            assert!(!cage().is_violated(&cells, 9));
+            cells[0].set_answer(1);
+            assert!(!cage().is_violated(&cells, 9));
+            cells[1].set_answer(4);
+            assert!(!cage().is_violated(&cells, 9));
+        }
+        #[test]
+        fn it_detects_wrong_sum() {
+            let mut cells = cells();
+            cells[0].set_answer(1);
+            cells[1].set_answer(3);
+            assert!(cage().is_violated(&cells, 9));
+            let mut cells = self::cells();
+            cells[0].set_answer(6);
+            assert!(cage().is_violated(&cells, 9));
+        }
+    }
+    mod text {
+        use super::*;
+        #[test]
+        fn it_parses_and_formats_the_cages() {
+            let text = "3:0,0 1,0|15:2,0 2,1 3,1";
+            let cages = parse_cages(text).unwrap();
+            assert_eq!(
+                cages,
+                vec![
+                    Cage::new(3, vec![Position::new(0, 0), Position::new(1, 0)]),
+                    Cage::new(
+                        15,
+                        vec![
+                            Position::new(2, 0),
+                            Position::new(2, 1),
+                            Position::new(3, 1)
+                        ]
+                    ),
+                ]
+            );
+            assert_eq!(cages_to_string(&cages), text);
+        }
+        #[test]
+        fn it_rejects_invalid_format() {
+            assert_eq!(
+                parse_cages("3:0,0|x:1,0").unwrap_err(),
+                CageError::InvalidFormat {
+                    text: "x:1,0".to_string()
+                }
+            );
+            assert!(parse_cages("3 0,0").is_err());
+            assert!(parse_cages("3:0;0").is_err());
+        }
+        #[test]
+        fn it_parses_empty_text() {
+            assert!(parse_cages("").unwrap().is_empty());
+        }
+    }
+    mod validate_cages {
+        use super::*;
+        fn validate(text: &str) -> Result<(), CageError> {
+            validate_cages(&parse_cages(text).unwrap(), 9, digits())
+        }
+        #[test]
+        fn it_accepts_valid_cages() {
+            assert_eq!(validate("3:0,0 1,0|15:2,0 2,1 3,1"), Ok(()));
+        }
+        #[test]
+        fn it_rejects_invalid_cages() {
+            assert_eq!(
+                validate("3:0,0 9,0"),
+                Err(CageError::OutOfRange {
+                    pos: Position::new(9, 0)
+                })
+            );
+            assert_eq!(
+                validate("3:0,0 1,0|5:1,0 2,0"),
+                Err(CageError::Overlap {
+                    pos: Position::new(1, 0)
+                })
+            );
+            assert_eq!(
+                validate("3:0,0 1,0|18:2,0 3,0"),
+                Err(CageError::ImpossibleSum { cage: 1, sum: 18 })
+            );
+            assert_eq!(
+                validate_cages(&[Cage::new(1, vec![])], 9, digits()),
+                Err(CageError::Empty { cage: 0 })
+            );
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use std::iter::FromIterator;
+
+/// A set of answer candidates stored as a bitmask.
+/// The n-th bit represents the number n, so numbers from 0 to 63 can be stored.
+///
+/// ビットマスクで保持する解答候補の集合。
+/// n 番目のビットが数値 n を表すため、0 から 63 までの数値を保持できる。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Candidates(u64);
+
+impl Candidates {
+    pub const MAX: u8 = 63;
+
+    /// Create an empty set.
+    pub fn new() -> Candidates {
+        Candidates(0)
+    }
+    pub fn insert(&mut self, candidate: u8) {
+        assert!(
+            candidate <= Self::MAX,
+            "The answer candidate must be less than or equal to {}.",
+            Self::MAX
+        );
+        self.0 |= 1 << candidate;
+    }
+    pub fn remove(&mut self, candidate: u8) {
+        if candidate <= Self::MAX {
+            self.0 &= !(1 << candidate);
+        }
+    }
+    pub fn contains(&self, candidate: u8) -> bool {
+        candidate <= Self::MAX && self.0 & (1 << candidate) != 0
+    }
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+    pub fn clear(&mut self) {
+        self.0 = 0;
+    }
+    pub fn intersection(&self, other: Candidates) -> Candidates {
+        Candidates(self.0 & other.0)
+    }
+    pub fn union(&self, other: Candidates) -> Candidates {
+        Candidates(self.0 | other.0)
+    }
+    pub fn difference(&self, other: Candidates) -> Candidates {
+        Candidates(self.0 & !other.0)
+    }
+    /// Returns the candidate if the set has only one candidate.
+    pub fn single(&self) -> Option<u8> {
+        if self.len() == 1 {
+            self.first()
+        } else {
+            None
+        }
+    }
+    /// Returns the smallest candidate.
+    pub fn first(&self) -> Option<u8> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.0.trailing_zeros() as u8)
+        }
+    }
+    /// Iterate the candidates in ascending order.
+    pub fn iter(&self) -> Iter {
+        Iter(self.0)
+    }
+    /// Iterate the references to the candidates in ascending order, like the iterator of a slice.
+    pub fn iter_ref(&self) -> RefIter {
+        RefIter(self.iter())
+    }
+}
+
+impl FromIterator<u8> for Candidates {
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Candidates {
+        let mut candidates = Candidates::new();
+        for candidate in iter {
+            candidates.insert(candidate);
+        }
+        candidates
+    }
+}
+
+impl<'a> FromIterator<&'a u8> for Candidates {
+    fn from_iter<I: IntoIterator<Item = &'a u8>>(iter: I) -> Candidates {
+        iter.into_iter().copied().collect()
+    }
+}
+
+impl IntoIterator for Candidates {
+    type Item = u8;
+    type IntoIter = Iter;
+    fn into_iter(self) -> Iter {
+        self.iter()
+    }
+}
+
+/// An iterator over the candidates of `Candidates` in ascending order.
+#[derive(Debug, Clone)]
+pub struct Iter(u64);
+
+impl Iterator for Iter {
+    type Item = u8;
+    fn next(&mut self) -> Option<u8> {
+        if self.0 == 0 {
+            return None;
+        }
+        let candidate = self.0.trailing_zeros() as u8;
+        self.0 &= self.0 - 1;
+        Some(candidate)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for Iter {
+    fn next_back(&mut self) -> Option<u8> {
+        if self.0 == 0 {
+            return None;
+        }
+        let candidate = 63 - self.0.leading_zeros() as u8;
+        self.0 &= !(1 << candidate);
+        Some(candidate)
+    }
+}
+
+impl ExactSizeIterator for Iter {}
+
+/// The numbers that the bits stand for, so that `RefIter` can yield references.
+static NUMBERS: [u8; 64] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
+    26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49,
+    50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63,
+];
+
+/// An iterator over the references to the candidates of `Candidates` in ascending order.
+/// It yields `&u8` like the iterator of a slice, which `Cell::answer_candidate` returned before the bitmask.
+///
+/// `Candidates` の解答候補の参照を昇順に返すイテレータ。
+/// ビットマスク以前に `Cell::answer_candidate` が返却していたスライスのイテレータと同様に `&u8` を返す。
+#[derive(Debug, Clone)]
+pub struct RefIter(Iter);
+
+impl Iterator for RefIter {
+    type Item = &'static u8;
+    fn next(&mut self) -> Option<&'static u8> {
+        self.0.next().map(|c| &NUMBERS[c as usize])
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for RefIter {
+    fn next_back(&mut self) -> Option<&'static u8> {
+        self.0.next_back().map(|c| &NUMBERS[c as usize])
+    }
+}
+
+impl ExactSizeIterator for RefIter {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn it_iterates_in_ascending_order() {
+        let candidates: Candidates = vec![9, 3, 25, 1].into_iter().collect();
+        assert_eq!(candidates.iter().collect::<Vec<u8>>(), vec![1, 3, 9, 25]);
+        assert_eq!(candidates.len(), 4);
+    }
+    #[test]
+    fn it_iterates_the_references_in_both_directions() {
+        let candidates: Candidates = vec![9, 3, 25, 1].into_iter().collect();
+        assert_eq!(
+            candidates.iter_ref().copied().collect::<Vec<u8>>(),
+            vec![1, 3, 9, 25]
+        );
+        assert_eq!(
+            candidates.iter_ref().rev().cloned().collect::<Vec<u8>>(),
+            vec![25, 9, 3, 1]
+        );
+        assert_eq!(candidates.iter_ref().len(), 4);
+    }
+    #[test]
+    fn it_can_insert_and_remove() {
+        let mut candidates = Candidates::new();
+        candidates.insert(4);
+        candidates.insert(63);
+        assert!(candidates.contains(4));
+        assert!(candidates.contains(63));
+        candidates.remove(4);
+        candidates.remove(4);
+        assert!(!candidates.contains(4));
+        assert_eq!(candidates.len(), 1);
+    }
+    #[test]
+    #[should_panic]
+    fn it_cannot_insert_over_max() {
+        Candidates::new().insert(64);
+    }
+    #[test]
+    fn set_operations() {
+        let a: Candidates = [1, 2, 3].iter().collect();
+        let b: Candidates = [2, 3, 4].iter().collect();
+        assert_eq!(a.intersection(b), [2, 3].iter().collect());
+        assert_eq!(a.union(b), [1, 2, 3, 4].iter().collect());
+        assert_eq!(a.difference(b), [1].iter().collect());
+    }
+    #[test]
+    fn single_returns_the_only_candidate() {
+        let a: Candidates = [7].iter().collect();
+        let b: Candidates = [7, 8].iter().collect();
+        assert_eq!(a.single(), Some(7));
+        assert_eq!(b.single(), None);
+        assert_eq!(Candidates::new().single(), None);
+    }
+}
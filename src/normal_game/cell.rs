@@ -1,16 +1,16 @@
+use crate::normal_game::candidates;
+use crate::normal_game::candidates::Candidates;
 use crate::normal_game::setting;
-use std::cell::RefCell;
-use std::rc::Rc;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub struct Cell {
     pos: Position,
-    answer_candidate: Vec<u8>,
+    answer_candidate: Candidates,
     answer: Option<u8>,
 }
 
 impl Cell {
-    pub fn new(pos: Position, answer_candidate: Vec<u8>) -> Cell {
+    pub fn new(pos: Position, answer_candidate: Candidates) -> Cell {
         Cell {
             pos,
             answer_candidate,
@@ -20,12 +20,24 @@ impl Cell {
     pub fn pos(&self) -> Position {
         self.pos
     }
+    /// Change the position of the cell.
+    /// A game looks up its cells by the index of the position, so this does not move the cell within a game.
+    ///
+    /// セルの Position を変更する。
+    /// ゲームは Position のインデックスでセルを参照するため、ゲーム内でセルが移動するわけではない。
     pub fn move_to(&mut self, pos: Position) {
         self.pos = pos;
     }
 
-    pub fn answer_candidate(&self) -> std::slice::Iter<u8> {
-        self.answer_candidate.iter()
+    /// Iterate the answer_candidate in ascending order.
+    /// It yields `&u8` like the iterator of a slice. Use `candidates` to get them as a set.
+    pub fn answer_candidate(&self) -> candidates::RefIter {
+        self.answer_candidate.iter_ref()
+    }
+
+    /// Returns the answer_candidate as a set.
+    pub fn candidates(&self) -> Candidates {
+        self.answer_candidate
     }
 
     pub fn answer(&self) -> Option<u8> {
@@ -34,16 +46,11 @@ impl Cell {
 
     /// Deletes the specified candidate answer.
     pub fn remove_answer_candidate(&mut self, target: u8) {
-        if let Ok(index) = self.answer_candidate.binary_search(&target) {
-            self.answer_candidate.remove(index);
-        }
+        self.answer_candidate.remove(target);
     }
 
     pub fn get_lonely(&self) -> Option<u8> {
-        if self.answer_candidate.len() == 1 {
-            return Some(self.answer_candidate[0]);
-        }
-        return None;
+        self.answer_candidate.single()
     }
 
     /// Fill in the Cell with your answer and clear the answer suggestions.
@@ -59,20 +66,20 @@ impl Cell {
     }
 
     pub fn has_answer_candidate(&self, candidate: u8) -> bool {
-        self.answer_candidate.iter().find(|a| **a == candidate) != None
+        self.answer_candidate.contains(candidate)
     }
 
     pub fn answer_candidate_count(&self) -> usize {
         self.answer_candidate.len()
     }
 
-    pub fn restore_answer_candidate(&mut self, answer_candidate: &Vec<u8>) {
-        self.answer_candidate = answer_candidate.clone();
+    pub fn restore_answer_candidate(&mut self, answer_candidate: Candidates) {
+        self.answer_candidate = answer_candidate;
     }
 }
 
 /// Position(x, y)
-#[derive(Debug, PartialEq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
 pub struct Position(u8, u8);
 impl Position {
     pub fn new(x: u8, y: u8) -> Position {
@@ -84,6 +91,17 @@ impl Position {
     pub fn y(&self) -> u8 {
         self.1
     }
+    /// Returns the index of the position in the flat array of cells.
+    pub fn index(&self, side_size: u8) -> usize {
+        self.1 as usize * side_size as usize + self.0 as usize
+    }
+    /// Returns the position of the index in the flat array of cells.
+    pub fn from_index(index: usize, side_size: u8) -> Position {
+        Position(
+            (index % side_size as usize) as u8,
+            (index / side_size as usize) as u8,
+        )
+    }
     pub fn move_x(&self, count: i16) -> Position {
         let x = match (self.0 as i16) + count {
             x if 0 <= x => x as u8,
@@ -101,14 +119,11 @@ impl Position {
     }
 }
 
-pub fn create_cells(setting: &setting::GameSetting) -> Vec<Rc<RefCell<Cell>>> {
+pub fn create_cells(setting: &setting::GameSetting) -> Vec<Cell> {
     let mut cells = Vec::new();
     for y in 0..setting.side_size() {
         for x in 0..setting.side_size() {
-            cells.push(Rc::new(RefCell::new(Cell::new(
-                Position(x, y),
-                setting.answer_candidate(),
-            ))));
+            cells.push(Cell::new(Position(x, y), setting.candidates()));
         }
     }
     cells
@@ -144,15 +159,15 @@ mod tests {
             }
             #[test]
             fn first_cell_position_is_0_0() {
-                assert_eq!(create_cells(&setting())[0].borrow().pos, Position(0, 0));
+                assert_eq!(create_cells(&setting())[0].pos, Position(0, 0));
             }
             #[test]
             fn second_cell_position_is_1_0() {
-                assert_eq!(create_cells(&setting())[1].borrow().pos, Position(1, 0));
+                assert_eq!(create_cells(&setting())[1].pos, Position(1, 0));
             }
             #[test]
             fn last_cell_position_is_5_5() {
-                assert_eq!(create_cells(&setting())[35].borrow().pos, Position(5, 5));
+                assert_eq!(create_cells(&setting())[35].pos, Position(5, 5));
             }
         }
         mod given_3_3 {
@@ -169,7 +184,7 @@ mod tests {
             }
             #[test]
             fn last_cell_position_is_8_8() {
-                assert_eq!(create_cells(&setting())[80].borrow().pos, Position(8, 8));
+                assert_eq!(create_cells(&setting())[80].pos, Position(8, 8));
             }
         }
     }
@@ -196,36 +211,59 @@ mod tests {
         fn test_move_y() {
             assert_eq!(Position::new(1, 2).move_y(3), Position::new(1, 5))
         }
+        #[test]
+        fn test_index() {
+            assert_eq!(Position::new(1, 2).index(6), 13);
+            assert_eq!(Position::from_index(13, 6), Position::new(1, 2));
+        }
     }
     mod test_cell_utilities {
         use super::*;
         #[test]
+        fn cell_can_move_to_another_position() {
+            let mut cell = Cell::new(Position(1, 1), setting().candidates());
+            cell.move_to(Position(2, 3));
+            assert_eq!(cell.pos(), Position(2, 3));
+        }
+        #[test]
         fn cell_can_remove_answer_candidate() {
-            let mut cell = Cell::new(Position(1, 1), setting().answer_candidate());
-            assert_eq!(cell.answer_candidate, [1, 2, 3, 4, 5, 6]);
+            let mut cell = Cell::new(Position(1, 1), setting().candidates());
+            assert_eq!(
+                cell.candidates().iter().collect::<Vec<u8>>(),
+                [1, 2, 3, 4, 5, 6]
+            );
             cell.remove_answer_candidate(4);
-            assert_eq!(cell.answer_candidate, [1, 2, 3, 5, 6]);
+            assert_eq!(
+                cell.candidates().iter().collect::<Vec<u8>>(),
+                [1, 2, 3, 5, 6]
+            );
             cell.remove_answer_candidate(4);
-            assert_eq!(cell.answer_candidate, [1, 2, 3, 5, 6]);
+            assert_eq!(
+                cell.candidates().iter().collect::<Vec<u8>>(),
+                [1, 2, 3, 5, 6]
+            );
         }
         #[test]
         fn test_remove_all_candidate() {
-            let mut cell = Cell::new(Position(1, 1), setting().answer_candidate());
-            assert_eq!(cell.answer_candidate, [1, 2, 3, 4, 5, 6]);
+            let mut cell = Cell::new(Position(1, 1), setting().candidates());
+            assert_eq!(
+                cell.candidates().iter().collect::<Vec<u8>>(),
+                [1, 2, 3, 4, 5, 6]
+            );
             cell.remove_answer_candidate(1);
             cell.remove_answer_candidate(2);
             cell.remove_answer_candidate(3);
             cell.remove_answer_candidate(4);
             cell.remove_answer_candidate(5);
             cell.remove_answer_candidate(6);
-            assert_eq!(cell.answer_candidate, []);
+            assert!(cell.answer_candidate.is_empty());
         }
         mod get_lonely {
             use super::*;
 
             #[test]
             fn test_get_lonely() {
-                let mut cell = Cell::new(Position(1, 1), setting().answer_candidate());
+                let mut cell = Cell::new(Position(1, 1), setting().candidates());
                 cell.remove_answer_candidate(1);
                 cell.remove_answer_candidate(2);
                 cell.remove_answer_candidate(4);
@@ -238,15 +276,18 @@ mod tests {
             use super::*;
             #[test]
             fn clear_candidate() {
-                let mut cell = Cell::new(Position(1, 1), setting().answer_candidate());
-                assert_eq!(cell.answer_candidate, [1, 2, 3, 4, 5, 6]);
+                let mut cell = Cell::new(Position(1, 1), setting().candidates());
+                assert_eq!(
+                    cell.candidates().iter().collect::<Vec<u8>>(),
+                    [1, 2, 3, 4, 5, 6]
+                );
                 cell.set_answer(4);
-                assert_eq!(cell.answer_candidate, []);
+                assert!(cell.answer_candidate.is_empty());
             }
         }
         #[test]
         fn has_answer_candidate_returns_true_when_candidate_not_exists() {
-            let mut cell = Cell::new(Position(1, 1), setting().answer_candidate());
+            let mut cell = Cell::new(Position(1, 1), setting().candidates());
             cell.remove_answer_candidate(1);
             cell.remove_answer_candidate(3);
             cell.remove_answer_candidate(5);
@@ -254,7 +295,7 @@ mod tests {
         }
         #[test]
         fn has_answer_candidate_returns_true_when_candidate_exists() {
-            let mut cell = Cell::new(Position(1, 1), setting().answer_candidate());
+            let mut cell = Cell::new(Position(1, 1), setting().candidates());
             cell.remove_answer_candidate(1);
             cell.remove_answer_candidate(3);
             cell.remove_answer_candidate(5);
@@ -264,7 +305,7 @@ mod tests {
             use super::*;
             #[test]
             fn return_answer_if_answered() {
-                let mut cell = Cell::new(Position(1, 1), setting().answer_candidate());
+                let mut cell = Cell::new(Position(1, 1), setting().candidates());
                 cell.set_answer(1);
                 assert_eq!(cell.answer(), Some(1));
             }
@@ -273,12 +314,12 @@ mod tests {
             use super::*;
             #[test]
             fn returns_6_when_cell_has_6_answer_candidate() {
-                let cell = Cell::new(Position(1, 1), setting().answer_candidate());
+                let cell = Cell::new(Position(1, 1), setting().candidates());
                 assert_eq!(cell.answer_candidate_count(), 6);
             }
             #[test]
             fn returns_true_when_cell_doesnt_have_answer_candidate() {
-                let mut cell = Cell::new(Position(1, 1), setting().answer_candidate());
+                let mut cell = Cell::new(Position(1, 1), setting().candidates());
                 cell.remove_answer_candidate(1);
                 cell.remove_answer_candidate(2);
                 cell.remove_answer_candidate(3);
@@ -292,21 +333,21 @@ mod tests {
             use super::*;
             #[test]
             fn it_will_become_none_after_deletion() {
-                let mut cell = Cell::new(Position(1, 1), setting().answer_candidate());
+                let mut cell = Cell::new(Position(1, 1), setting().candidates());
                 cell.set_answer(3);
                 cell.remove_answer();
                 assert_eq!(cell.answer(), None);
             }
             #[test]
             fn it_return_the_removed_answer() {
-                let mut cell = Cell::new(Position(1, 1), setting().answer_candidate());
+                let mut cell = Cell::new(Position(1, 1), setting().candidates());
                 cell.set_answer(3);
                 let removed_answer = cell.remove_answer();
                 assert_eq!(removed_answer, Some(3));
             }
             #[test]
             fn it_return_none_when_no_answer() {
-                let mut cell = Cell::new(Position(1, 1), setting().answer_candidate());
+                let mut cell = Cell::new(Position(1, 1), setting().candidates());
                 let removed_answer = cell.remove_answer();
                 assert_eq!(removed_answer, None);
             }
@@ -315,12 +356,12 @@ mod tests {
             use super::*;
             #[test]
             fn it_restore_answer_candidate() {
-                let mut cell = Cell::new(Position(1, 1), setting().answer_candidate());
+                let mut cell = Cell::new(Position(1, 1), setting().candidates());
                 cell.remove_answer_candidate(1);
                 cell.remove_answer_candidate(2);
                 cell.remove_answer_candidate(3);
-                cell.restore_answer_candidate(&vec![2, 4, 6]);
-                assert_eq!(cell.answer_candidate, [2, 4, 6]);
+                cell.restore_answer_candidate([2, 4, 6].iter().collect());
+                assert_eq!(cell.candidates().iter().collect::<Vec<u8>>(), [2, 4, 6]);
             }
         }
     }
@@ -0,0 +1,84 @@
+use crate::normal_game::technique::Technique;
+use crate::normal_game::NormalGame;
+
+/// The difficulty of a game, defined by the techniques required to solve it.
+///
+/// - Easy: Hidden singles only.
+/// - Medium: Naked singles, pointing, claiming, naked pairs or hidden pairs are also required.
+/// - Hard: Naked or hidden triples and quads, fish such as X-Wing, wings or simple coloring are also required.
+/// - Expert: X-Chains or XY-Chains are also required,
+///   or the techniques are not enough and guessing, including trial and error, is required.
+///
+/// ゲームを解くために必要なテクニックで定義する難易度。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl Difficulty {
+    /// Returns the techniques allowed for a game of this difficulty, in ascending order of difficulty.
+    ///
+    /// この難易度のゲームで使用できるテクニックを難易度の昇順に返却する。
+    pub fn techniques(&self) -> Vec<Technique> {
+        Technique::ALL
+            .iter()
+            .filter(|t| t.difficulty() <= *self)
+            .copied()
+            .collect()
+    }
+}
+
+impl NormalGame {
+    /// Returns the difficulty of the game.
+    /// It assumes that the game has only one solution.
+    ///
+    /// ゲームの難易度を返却する。
+    /// ゲームの解答がただ 1 つであることを前提とする。
+    pub fn difficulty(&self) -> Difficulty {
+        self.rate().difficulty
+    }
+
+    /// Returns true if the game can be solved with the techniques allowed for the difficulty.
+    ///
+    /// 指定した難易度で使用できるテクニックでゲームを解ける場合に true を返す。
+    pub fn is_solvable_within(&self, difficulty: Difficulty) -> bool {
+        if difficulty == Difficulty::Expert {
+            return self.has_unique_solution();
+        }
+        self.solve_logically(&difficulty.techniques()).is_solved()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normal_game::setting::BlockSize;
+    use crate::normal_game::setting::GameSetting;
+    fn game(issue: &str) -> NormalGame {
+        let mut game = NormalGame::new(GameSetting::new(BlockSize {
+            height: 3,
+            width: 3,
+        }));
+        game.load(issue);
+        game
+    }
+    #[test]
+    fn techniques_are_filtered_by_difficulty() {
+        assert_eq!(Difficulty::Easy.techniques(), vec![Technique::HiddenSingle]);
+        assert_eq!(Difficulty::Expert.techniques(), Technique::ALL.to_vec());
+    }
+    #[test]
+    fn solved_game_is_easy() {
+        let game = game("174392865|682715943|935468721|528176439|417839652|369254187|893541276|746923518|251687394");
+        assert_eq!(game.difficulty(), Difficulty::Easy);
+    }
+    #[test]
+    fn it_rates_the_game() {
+        let game = game(" 7     6 |6   1   3|  54 87  |  8   4  | 1  3  5 |  9   1  |  35 12  |7   2   8| 5     9 ");
+        assert!(game.difficulty() <= Difficulty::Medium);
+        assert!(game.is_solvable_within(game.difficulty()));
+    }
+}
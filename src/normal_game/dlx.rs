@@ -0,0 +1,574 @@
+use crate::normal_game::budget::Budget;
+use crate::normal_game::budget::GaveUp;
+use crate::normal_game::candidates::Candidates;
+use crate::normal_game::GameState;
+use crate::normal_game::NormalGame;
+use rand::prelude::*;
+
+impl NormalGame {
+    /// Solve the game as an exact cover problem with Dancing Links (Algorithm X).
+    /// The matrix is built from the answer_candidate left after the same eliminations as `solve`,
+    /// and the search does not clone the game for each guess.
+    /// If the game has a unique solution, the result is the same as `solve`.
+    /// If the problem is inconsistent, it returns None.
+    ///
+    /// Dancing Links (Algorithm X) を用いて、ゲームを完全被覆問題として解く。
+    /// 行列は `solve` と同じ除去を行った後の answer_candidate から作成し、探索では仮定のたびにゲームを複製しない。
+    /// ゲームの解答がただ 1 つである場合、結果は `solve` と同じである。
+    /// 問題に矛盾がある場合は None を返す。
+    pub fn solve_with_dlx(&self) -> Option<NormalGame> {
+        self.solve_with_dlx_within(&Budget::new()).unwrap()
+    }
+
+    /// Solve the game with Dancing Links within the budget.
+    ///
+    /// Dancing Links を用いて、上限の範囲でゲームを解く。
+    pub(crate) fn solve_with_dlx_within(
+        &self,
+        budget: &Budget,
+    ) -> Result<Option<NormalGame>, GaveUp> {
+        let game = match self.propagated() {
+            Some(game) => game,
+            None => return Ok(None),
+        };
+        self.first_solution(Dlx::new(&game, budget))
+    }
+
+    /// Solve the game with Dancing Links, trying the answer_candidate in random order.
+    /// It is used to generate various solved games of the variants, which cannot be shuffled.
+    ///
+    /// Dancing Links を用いて、answer_candidate をランダムな順に試してゲームを解く。
+    /// シャッフルできないバリアントについて、様々な解答済みのゲームを生成するために使用する。
+    pub(crate) fn solve_with_dlx_randomly<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        budget: &Budget,
+    ) -> Result<Option<NormalGame>, GaveUp> {
+        let game = match self.propagated() {
+            Some(game) => game,
+            None => return Ok(None),
+        };
+        self.first_solution(Dlx::new_with_row_order(&game, budget, |rows| {
+            rows.shuffle(rng)
+        }))
+    }
+
+    /// Fill in the singles and remove the answer_candidate with the elimination techniques, as `solve` does before guessing.
+    /// Algorithm X alone only finds the singles, so the search is much smaller from the propagated candidates.
+    /// It returns None if the game turns out to be inconsistent.
+    ///
+    /// `solve` が仮定の前に行うのと同様に、唯一の解答を記入し、除去のテクニックで answer_candidate を除去する。
+    /// Algorithm X だけでは唯一の解答しか見つけられないため、除去後の候補から探索する方が探索は非常に小さくなる。
+    /// ゲームに矛盾があると判明した場合は None を返却する。
+    fn propagated(&self) -> Option<NormalGame> {
+        let mut game = self.clone();
+        match game.fill_lonely_and_eliminate() {
+            GameState::Failure => None,
+            _ => Some(game),
+        }
+    }
+
+    fn first_solution(&self, mut dlx: Dlx) -> Result<Option<NormalGame>, GaveUp> {
+        let mut found: Option<Vec<Row>> = None;
+        dlx.search(&mut |rows| {
+            found = Some(rows.to_vec());
+            false
+        })?;
+        Ok(found.map(|rows| self.fill_rows(&rows)))
+    }
+
+    /// Count the solutions of the game with Dancing Links, up to `limit`.
+    /// The result is the same as `count_solutions`.
+    ///
+    /// Dancing Links を用いて、ゲームの解答の数を `limit` を上限として数える。
+    /// 結果は `count_solutions` と同じである。
+    pub fn count_solutions_with_dlx(&self, limit: usize) -> usize {
+        let mut count = 0;
+        if limit == 0 {
+            return count;
+        }
+        let game = match self.propagated() {
+            Some(game) => game,
+            None => return count,
+        };
+        Dlx::new(&game, &Budget::new())
+            .search(&mut |_| {
+                count += 1;
+                count < limit
+            })
+            .unwrap();
+        count
+    }
+
+    /// Returns the solutions of the game found with Dancing Links within the budget, up to `limit`.
+    ///
+    /// Dancing Links を用いて上限の範囲で見つけたゲームの解答を、`limit` を上限として返却する。
+    pub(crate) fn solutions_with_dlx_within(
+        &self,
+        limit: usize,
+        budget: &Budget,
+    ) -> Result<Vec<NormalGame>, GaveUp> {
+        let mut found: Vec<Vec<Row>> = vec![];
+        if limit == 0 {
+            return Ok(vec![]);
+        }
+        let game = match self.propagated() {
+            Some(game) => game,
+            None => return Ok(vec![]),
+        };
+        Dlx::new(&game, budget).search(&mut |rows| {
+            found.push(rows.to_vec());
+            found.len() < limit
+        })?;
+        Ok(found.iter().map(|rows| self.fill_rows(rows)).collect())
+    }
+
+    fn fill_rows(&self, rows: &[Row]) -> NormalGame {
+        let mut game = self.clone();
+        for (index, answer) in rows.iter() {
+            let pos = game.cells()[*index].pos();
+            game.set_answer(pos, *answer);
+        }
+        game
+    }
+}
+
+/// A row of the exact cover problem, which is the index of the cell and its answer.
+///
+/// 完全被覆問題の行。セルのインデックスとその解答の組である。
+type Row = (usize, u8);
+
+/// The root of the header list.
+const ROOT: usize = 0;
+
+/// The toroidal doubly linked lists of Dancing Links.
+/// Nodes `1..=columns` are the column headers and the rest are the nodes of the rows.
+/// Each cell is a column that is covered once, and each pair of a group and an answer_candidate
+/// is a column that is covered once, because every group has every answer_candidate exactly once.
+///
+/// Dancing Links の環状の双方向リスト。
+/// ノード `1..=columns` は列のヘッダであり、残りは行のノードである。
+/// 各セルは 1 度だけ被覆される列である。また、全てのグループは全ての answer_candidate をちょうど 1 つずつ持つため、
+/// グループと answer_candidate の組も 1 度だけ被覆される列である。
+struct Dlx {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    column: Vec<usize>,
+    row: Vec<usize>,
+    size: Vec<usize>,
+    rows: Vec<Row>,
+    budget: Budget,
+    guesses: usize,
+    gave_up: bool,
+    constraints: Constraints,
+}
+
+impl Dlx {
+    fn new(game: &NormalGame, budget: &Budget) -> Dlx {
+        Self::new_with_row_order(game, budget, |_| {})
+    }
+
+    /// Create the lists after `order` sorts the rows, which determines the order in which the rows are tried.
+    ///
+    /// `order` で行を並べ替えた後にリストを作成する。行を試す順序はこの順序で決まる。
+    fn new_with_row_order(
+        game: &NormalGame,
+        budget: &Budget,
+        order: impl FnOnce(&mut Vec<(Row, Vec<usize>)>),
+    ) -> Dlx {
+        let answer_candidate = game.setting().answer_candidate();
+        let cell_count = game.cells().len();
+        let columns = cell_count + game.groups().len() * answer_candidate.len();
+        let mut dlx = Dlx {
+            left: (0..=columns)
+                .map(|i| if i == 0 { columns } else { i - 1 })
+                .collect(),
+            right: (0..=columns).map(|i| (i + 1) % (columns + 1)).collect(),
+            up: (0..=columns).collect(),
+            down: (0..=columns).collect(),
+            column: (0..=columns).collect(),
+            row: vec![0; columns + 1],
+            size: vec![0; columns + 1],
+            rows: vec![],
+            budget: budget.clone(),
+            guesses: 0,
+            gave_up: false,
+            constraints: Constraints::new(game),
+        };
+        let mut rows: Vec<(Row, Vec<usize>)> = vec![];
+        for (index, cell) in game.cells().iter().enumerate() {
+            let answers = match cell.answer() {
+                Some(answer) => vec![answer],
+                None => NormalGame::ordered_answer_candidate(game, cell.candidates()),
+            };
+            for answer in answers {
+                let digit = match answer_candidate.iter().position(|a| *a == answer) {
+                    Some(digit) => digit,
+                    None => continue,
+                };
+                let mut row_columns = vec![1 + index];
+                row_columns.extend(
+                    game.groups_of(index)
+                        .iter()
+                        .map(|g| 1 + cell_count + g * answer_candidate.len() + digit),
+                );
+                rows.push(((index, answer), row_columns));
+            }
+        }
+        order(&mut rows);
+        for (row, row_columns) in rows.iter() {
+            dlx.add_row(*row, row_columns);
+        }
+        dlx
+    }
+
+    fn add_row(&mut self, row: Row, columns: &[usize]) {
+        let row_index = self.rows.len();
+        self.rows.push(row);
+        let first = self.left.len();
+        for (i, column) in columns.iter().enumerate() {
+            let node = first + i;
+            let last = if i == 0 { node } else { node - 1 };
+            self.left.push(last);
+            self.right.push(first);
+            self.right[last] = node;
+            self.left[first] = node;
+            self.up.push(self.up[*column]);
+            self.down.push(*column);
+            let up = self.up[*column];
+            self.down[up] = node;
+            self.up[*column] = node;
+            self.column.push(*column);
+            self.row.push(row_index);
+            self.size[*column] += 1;
+        }
+    }
+
+    fn cover(&mut self, column: usize) {
+        self.right[self.left[column]] = self.right[column];
+        self.left[self.right[column]] = self.left[column];
+        let mut i = self.down[column];
+        while i != column {
+            let mut j = self.right[i];
+            while j != i {
+                self.down[self.up[j]] = self.down[j];
+                self.up[self.down[j]] = self.up[j];
+                self.size[self.column[j]] -= 1;
+                j = self.right[j];
+            }
+            i = self.down[i];
+        }
+    }
+
+    fn uncover(&mut self, column: usize) {
+        let mut i = self.up[column];
+        while i != column {
+            let mut j = self.left[i];
+            while j != i {
+                self.size[self.column[j]] += 1;
+                self.down[self.up[j]] = j;
+                self.up[self.down[j]] = j;
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+        self.right[self.left[column]] = column;
+        self.left[self.right[column]] = column;
+    }
+
+    /// Returns the number of the rows of the column that the constraints accept, counting up to `limit`.
+    /// Without the constraints, it is the size of the column.
+    fn available_size(&self, column: usize, limit: usize) -> usize {
+        if !self.constraints.is_active() {
+            return self.size[column];
+        }
+        let mut count = 0;
+        let mut r = self.down[column];
+        while r != column && count < limit {
+            if self.constraints.accepts(self.rows[self.row[r]]) {
+                count += 1;
+            }
+            r = self.down[r];
+        }
+        count
+    }
+
+    /// Search the exact covers and call `on_solution` with the rows of each of them.
+    /// The search stops when `on_solution` returns false, or returns Err when the budget has run out.
+    /// Trying a row of a column that has two or more rows is counted as a guess.
+    ///
+    /// 完全被覆を探索し、それぞれの行を引数として `on_solution` を呼び出す。
+    /// `on_solution` が false を返した場合は探索を打ち切り、上限に達した場合は Err を返却する。
+    /// 2 つ以上の行を持つ列の行を試すことを推測として数える。
+    fn search(&mut self, on_solution: &mut dyn FnMut(&[Row]) -> bool) -> Result<(), GaveUp> {
+        let mut solution: Vec<usize> = vec![];
+        self.search_from(&mut solution, on_solution);
+        if self.gave_up {
+            Err(GaveUp)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn search_from(
+        &mut self,
+        solution: &mut Vec<usize>,
+        on_solution: &mut dyn FnMut(&[Row]) -> bool,
+    ) -> bool {
+        if self.right[ROOT] == ROOT {
+            let rows: Vec<Row> = solution.iter().map(|r| self.rows[*r]).collect();
+            return on_solution(&rows);
+        }
+        // Choose the column with the fewest rows to keep the search tree small.
+        let mut column = self.right[ROOT];
+        let mut column_size = self.available_size(column, usize::MAX);
+        let mut c = self.right[column];
+        while c != ROOT && column_size != 0 {
+            let size = self.available_size(c, column_size);
+            if size < column_size {
+                column = c;
+                column_size = size;
+            }
+            c = self.right[c];
+        }
+        if column_size == 0 {
+            return true;
+        }
+        let guessing = column_size > 1;
+        self.cover(column);
+        let mut keep_searching = true;
+        let mut r = self.down[column];
+        while r != column {
+            let row = self.rows[self.row[r]];
+            if !self.constraints.accepts(row) {
+                r = self.down[r];
+                continue;
+            }
+            if guessing {
+                self.guesses += 1;
+                if self.budget.check(self.guesses).is_err() {
+                    self.gave_up = true;
+                    keep_searching = false;
+                    break;
+                }
+            }
+            solution.push(self.row[r]);
+            self.constraints.place(row);
+            let mut j = self.right[r];
+            while j != r {
+                self.cover(self.column[j]);
+                j = self.right[j];
+            }
+            keep_searching = self.search_from(solution, on_solution);
+            let mut j = self.left[r];
+            while j != r {
+                self.uncover(self.column[j]);
+                j = self.left[j];
+            }
+            solution.pop();
+            self.constraints.unplace(row);
+            if !keep_searching {
+                break;
+            }
+            r = self.down[r];
+        }
+        self.uncover(column);
+        keep_searching
+    }
+}
+
+/// The constraints that are not expressed as the columns of the exact cover problem:
+/// the peers that do not share a group, and the sums of the cages.
+/// They are checked when a row is tried, and the rows that break them are skipped.
+///
+/// 完全被覆問題の列として表現されない制約。グループを共有しないピアと、ケージの合計である。
+/// 行を試す際に確認し、制約に反する行は読み飛ばす。
+struct Constraints {
+    peers: Vec<Vec<usize>>,
+    cell_cages: Vec<Vec<usize>>,
+    cage_sizes: Vec<usize>,
+    cage_targets: Vec<u32>,
+    digits: Vec<u8>,
+    answers: Vec<Option<u8>>,
+    cage_used: Vec<Candidates>,
+    cage_sums: Vec<u32>,
+    active: bool,
+}
+
+impl Constraints {
+    fn new(game: &NormalGame) -> Constraints {
+        let cell_count = game.cells().len();
+        let peers: Vec<Vec<usize>> = (0..cell_count)
+            .map(|index| {
+                game.peers_of(index)
+                    .iter()
+                    .copied()
+                    .filter(|p| {
+                        !game
+                            .groups_of(index)
+                            .iter()
+                            .any(|g| game.groups_of(*p).contains(g))
+                    })
+                    .collect()
+            })
+            .collect();
+        let cages = game.setting().cages();
+        let mut digits = game.setting().answer_candidate();
+        digits.sort_unstable();
+        let active = !cages.is_empty() || peers.iter().any(|p| !p.is_empty());
+        Constraints {
+            active,
+            peers,
+            cell_cages: game.cell_cages.to_vec(),
+            cage_sizes: cages.iter().map(|c| c.cells().len()).collect(),
+            cage_targets: cages.iter().map(|c| c.sum()).collect(),
+            digits,
+            answers: vec![None; cell_count],
+            cage_used: vec![Candidates::new(); cages.len()],
+            cage_sums: vec![0; cages.len()],
+        }
+    }
+
+    /// Returns false if a peer already has the answer, or the cages can no longer add up to their sums.
+    fn accepts(&self, (index, answer): Row) -> bool {
+        if self.peers[index]
+            .iter()
+            .any(|p| self.answers[*p] == Some(answer))
+        {
+            return false;
+        }
+        self.cell_cages[index].iter().all(|c| {
+            if self.cage_used[*c].contains(answer) {
+                return false;
+            }
+            let mut used = self.cage_used[*c];
+            used.insert(answer);
+            let sum = self.cage_sums[*c] + answer as u32;
+            let target = self.cage_targets[*c];
+            if sum > target {
+                return false;
+            }
+            // The rest of the cage must be able to add up to the rest of the sum.
+            let remaining = self.cage_sizes[*c] - used.len();
+            let rest = self.digits.iter().filter(|d| !used.contains(**d));
+            let min: u32 = rest.clone().take(remaining).map(|d| *d as u32).sum();
+            let max: u32 = rest.rev().take(remaining).map(|d| *d as u32).sum();
+            (min..=max).contains(&(target - sum))
+        })
+    }
+
+    /// Returns true if there are any constraints to check.
+    fn is_active(&self) -> bool {
+        self.active
+    }
+
+    fn place(&mut self, (index, answer): Row) {
+        self.answers[index] = Some(answer);
+        for c in self.cell_cages[index].iter() {
+            self.cage_used[*c].insert(answer);
+            self.cage_sums[*c] += answer as u32;
+        }
+    }
+
+    fn unplace(&mut self, (index, answer): Row) {
+        self.answers[index] = None;
+        for c in self.cell_cages[index].iter() {
+            self.cage_used[*c].remove(answer);
+            self.cage_sums[*c] -= answer as u32;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normal_game::setting::BlockSize;
+    use crate::normal_game::setting::GameSetting;
+    use crate::normal_game::GameState;
+    fn game(height: u8, width: u8, issue: &str) -> NormalGame {
+        let mut game = NormalGame::new(GameSetting::new(BlockSize { height, width }));
+        game.load(issue);
+        game
+    }
+    mod solve_with_dlx {
+        use super::*;
+        #[test]
+        fn it_returns_the_same_solution_as_solve() {
+            let issues = [
+                " 7     6 |6   1   3|  54 87  |  8   4  | 1  3  5 |  9   1  |  35 12  |7   2   8| 5     9 ",
+                "   2  861|  5  1   |      4  |8  47    | 2 18 3  | 34      |  7      |9  5    2| 8 6  1   ",
+                "  3 2 6  |9  3 5  1|  18 64  |  81 29  |7       8|  67 82  |  26 95  |8  2 3  9|  5 1 3  ",
+            ];
+            for issue in issues.iter() {
+                let game = game(3, 3, issue);
+                assert_eq!(game.count_solutions_with_dlx(2), 1);
+                assert_eq!(
+                    game.solve_with_dlx().unwrap().to_string(),
+                    game.solve().unwrap().to_string()
+                );
+            }
+        }
+        #[test]
+        fn it_solves_16x16() {
+            let game = game(4, 4, "7, , ,11,4, , ,10,2, , ,1,12, , ,5| , ,6, , ,3, , , , ,16, , ,10| ,10,14, , ,13,7, , ,5,6, , ,3,1,|2, , , , , , ,15,13, , , , , , ,14|8, , , , , , ,1,12, , , , , , ,11| ,13,12, , ,8,15, , ,9,5, , ,14,16| , ,10, , ,2, , , , ,11, , ,7,|15, , ,9,5, , ,12,4, , ,2,6, , ,8|10, , ,5,1, , ,16,15, , ,9,8, , ,4| , ,8, , ,10, , , , ,1, , ,6| ,7,11, , ,4,8, , ,14,12, , ,5,3|4, , , , , , ,5,7, , , , , , ,10|9, , , , , , ,14,10, , , , , , ,1| ,6,2, , ,7,5, , ,11,9, , ,4,8| , ,13, , ,15, , , , ,3, , ,16|16, , ,3,10, , ,4,6, , ,14,13, , ,12");
+            let mut solved = game.solve_with_dlx().unwrap();
+            assert_eq!(solved.check_status(), GameState::Complete);
+            assert_eq!(solved.count_solutions_with_dlx(2), 1);
+        }
+        #[test]
+        fn it_solves_empty_25x25() {
+            let mut solved = NormalGame::new(GameSetting::new(BlockSize {
+                height: 5,
+                width: 5,
+            }))
+            .solve_with_dlx()
+            .unwrap();
+            assert_eq!(solved.check_status(), GameState::Complete);
+        }
+        #[test]
+        fn it_solves_generated_large_issues_with_few_guesses() {
+            // Plain Algorithm X took minutes on the 5x5 issue of seed 1, because it only finds the singles.
+            for (height, width) in [(4, 5), (5, 5)].iter() {
+                let (issue, solved) = NormalGame::generate_with_seed(
+                    BlockSize {
+                        height: *height,
+                        width: *width,
+                    },
+                    1,
+                );
+                let budget = Budget::new().max_guesses(10);
+                assert!(issue.solve_with_dlx_within(&budget).unwrap().unwrap() == solved);
+                assert_eq!(issue.count_solutions_with_dlx(2), 1);
+            }
+        }
+        #[test]
+        fn it_returns_none_for_inconsistent_game() {
+            assert!(game(3, 3, "11").solve_with_dlx().is_none());
+        }
+    }
+    mod count_solutions_with_dlx {
+        use super::*;
+        #[test]
+        fn it_counts_the_same_as_count_solutions() {
+            let game = game(
+                3,
+                3,
+                "  2 9 1|   1   8|3    6  2| 4    7|6       1|  5    9|9  7    4| 6   3|  7 2 5 3",
+            );
+            assert_eq!(
+                game.count_solutions_with_dlx(100),
+                game.count_solutions(100)
+            );
+            assert!(game.count_solutions_with_dlx(100) > 1);
+        }
+        #[test]
+        fn it_stops_at_the_limit() {
+            let game = game(2, 2, "");
+            assert_eq!(game.count_solutions_with_dlx(0), 0);
+            assert_eq!(game.count_solutions_with_dlx(5), 5);
+            assert_eq!(game.count_solutions_with_dlx(1000), 288);
+        }
+    }
+}
@@ -1,17 +1,42 @@
+use crate::normal_game::candidates::Candidates;
 use crate::normal_game::cell;
 use crate::normal_game::setting;
-use std::cell::RefCell;
-use std::collections::HashSet;
-use std::rc::Rc;
+use std::sync::Arc;
 
-// #[derive(Debug)]
+/// The kind of cells that a group is made of.
+///
+/// グループを構成するセルの種類。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GroupKind {
+    Row,
+    Column,
+    Block,
+    Diagonal,
+    Window,
+}
+
+#[derive(Debug, Clone)]
 pub struct Group {
-    cells: Vec<Rc<RefCell<cell::Cell>>>,
-    answer_candidate: Vec<u8>,
+    kind: GroupKind,
+    cells: Arc<[usize]>,
+    answer_candidate: Candidates,
 }
 
 impl Group {
-    pub fn cells(&self) -> &Vec<Rc<RefCell<cell::Cell>>> {
+    fn new(kind: GroupKind, cells: Vec<usize>, setting: &setting::GameSetting) -> Group {
+        Group {
+            kind,
+            cells: cells.into(),
+            answer_candidate: setting.candidates(),
+        }
+    }
+
+    pub fn kind(&self) -> GroupKind {
+        self.kind
+    }
+
+    /// Returns the indexes of the cells that belong to the group.
+    pub fn cells(&self) -> &[usize] {
         &self.cells
     }
 
@@ -20,168 +45,230 @@ impl Group {
     /// Returns the answer_candidate and the position of the cell that is held by only one of the cells that belong to the group.
     ///
     /// Group に所属する Cell の うち 1 つの Cell のみが保有している answer_candidate とその Cell の Position を返却する。
-    pub fn get_lonely(&self) -> Vec<(cell::Position, u8)> {
+    pub fn get_lonely(&self, cells: &[cell::Cell]) -> Vec<(cell::Position, u8)> {
+        // Collect the candidates held by at least one cell and by two or more cells.
+        let mut once = Candidates::new();
+        let mut twice = Candidates::new();
+        for c in self.cells.iter() {
+            let candidates = cells[*c].candidates();
+            twice = twice.union(once.intersection(candidates));
+            once = once.union(candidates);
+        }
+        let lonely = self.answer_candidate.intersection(once.difference(twice));
+        if lonely.is_empty() {
+            return vec![];
+        }
         let mut lonelies: Vec<(cell::Position, u8)> = vec![];
-        for candidate in self.answer_candidate.iter() {
-            let cells: Vec<cell::Position> = self
+        for candidate in lonely.iter() {
+            if let Some(c) = self
                 .cells
                 .iter()
-                .filter(|c| c.borrow().has_answer_candidate(*candidate))
-                .map(|c| c.borrow().pos())
-                .collect();
-            if cells.len() == 1 {
-                lonelies.push((cells[0], *candidate));
+                .map(|c| &cells[*c])
+                .find(|c| c.has_answer_candidate(candidate))
+            {
+                lonelies.push((c.pos(), candidate));
             }
         }
         lonelies
     }
 
     /// Remove the specified answer from the unanswerd_candidate.
+    /// The answer_candidate of the cells is updated by NormalGame through the peers of the cell.
     pub fn remove_answer_candidate(&mut self, answer: u8) {
-        self.answer_candidate = self
-            .answer_candidate
-            .iter()
-            .filter(|n| **n != answer)
-            .map(|n| *n)
-            .collect();
-        self.cells
-            .iter()
-            .for_each(|c| c.borrow_mut().remove_answer_candidate(answer));
+        self.answer_candidate.remove(answer);
     }
 
     pub fn is_all_clear_answer_candidate(&self) -> bool {
-        self.answer_candidate.len() == 0
+        self.answer_candidate.is_empty()
     }
 
-    pub fn is_duplicate_answer(&self) -> bool {
-        let answers: Vec<u8> = self
-            .cells()
-            .iter()
-            .map(|c| c.borrow().answer())
-            .filter(|a| a.is_some())
-            .map(|a| a.unwrap())
-            .collect();
-        let answers_len = answers.len();
-        let answers_hash: HashSet<u8> = answers.into_iter().collect();
-        return answers_len != answers_hash.len();
+    pub fn is_duplicate_answer(&self, cells: &[cell::Cell]) -> bool {
+        let mut answers = Candidates::new();
+        for answer in self.cells.iter().filter_map(|c| cells[*c].answer()) {
+            if answers.contains(answer) {
+                return true;
+            }
+            answers.insert(answer);
+        }
+        false
     }
 
+    /// Returns the answer_candidate in ascending order.
     pub fn answer_candidate(&self) -> Vec<u8> {
-        self.answer_candidate.clone()
+        self.answer_candidate.iter().collect()
     }
 
-    pub fn restore_answer_candidate(&mut self, answer_candidate: u8) {
-        if self
-            .answer_candidate
-            .iter()
-            .find(|a| **a == answer_candidate)
-            .is_some()
-        {
+    pub fn candidates(&self) -> Candidates {
+        self.answer_candidate
+    }
+
+    pub fn restore_answer_candidate(&mut self, answer_candidate: u8, cells: &[cell::Cell]) {
+        if self.answer_candidate.contains(answer_candidate) {
             return;
         }
         if self
-            .cells()
+            .cells
             .iter()
-            .map(|c| c.borrow().answer())
-            .filter(|answer| answer.is_some())
-            .map(|answer| answer.unwrap())
-            .any(|answer| answer == answer_candidate)
+            .any(|c| cells[*c].answer() == Some(answer_candidate))
         {
             return;
         }
-        self.answer_candidate.push(answer_candidate);
+        self.answer_candidate.insert(answer_candidate);
     }
 
-    pub fn answer_count(&self) -> usize {
-        let cells: Vec<&Rc<RefCell<cell::Cell>>> = self
-            .cells()
+    pub fn answer_count(&self, cells: &[cell::Cell]) -> usize {
+        self.cells
             .iter()
-            .filter(|c| c.borrow().answer().is_some())
-            .collect();
-        cells.len()
+            .filter(|c| cells[**c].answer().is_some())
+            .count()
     }
 }
 
-pub fn create_groups(
-    cells: &Vec<Rc<RefCell<cell::Cell>>>,
-    setting: &setting::GameSetting,
-) -> Vec<Rc<RefCell<Group>>> {
-    let hg = create_horizontal_groups(&cells, &setting);
-    let vg = create_vertical_groups(&cells, &setting);
-    let gg = create_block_groups(&cells, &setting);
-    vec![hg, vg, gg]
+pub fn create_groups(setting: &setting::GameSetting) -> Vec<Group> {
+    let hg = create_horizontal_groups(setting);
+    let vg = create_vertical_groups(setting);
+    let gg = create_block_groups(setting);
+    let dg = if setting.has_diagonals() {
+        create_diagonal_groups(setting)
+    } else {
+        vec![]
+    };
+    let wg = if setting.has_windows() {
+        create_window_groups(setting)
+    } else {
+        vec![]
+    };
+    vec![hg, vg, gg, dg, wg].into_iter().flatten().collect()
+}
+
+/// Returns the indexes of the groups that each cell belongs to.
+pub fn create_cell_groups(groups: &[Group], cell_count: usize) -> Vec<Vec<usize>> {
+    let mut cell_groups: Vec<Vec<usize>> = vec![vec![]; cell_count];
+    for (g, group) in groups.iter().enumerate() {
+        for c in group.cells().iter() {
+            cell_groups[*c].push(g);
+        }
+    }
+    cell_groups
+}
+
+/// Returns the indexes of the other cells that share a group with each cell.
+pub fn create_peers(groups: &[Group], cell_groups: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    cell_groups
         .iter()
-        .flatten()
-        .map(|g| g.clone())
+        .enumerate()
+        .map(|(index, group_indexes)| {
+            let mut peers: Vec<usize> = vec![];
+            for g in group_indexes.iter() {
+                for c in groups[*g].cells().iter() {
+                    if *c != index && !peers.contains(c) {
+                        peers.push(*c);
+                    }
+                }
+            }
+            peers
+        })
         .collect()
 }
 
-fn create_vertical_groups(
-    cells: &Vec<Rc<RefCell<cell::Cell>>>,
-    setting: &setting::GameSetting,
-) -> Vec<Rc<RefCell<Group>>> {
-    let x_pos: Vec<u8> = (0..setting.side_size()).collect();
-    x_pos
-        .iter()
+fn create_vertical_groups(setting: &setting::GameSetting) -> Vec<Group> {
+    let side_size = setting.side_size();
+    (0..side_size)
         .map(|x| {
-            Rc::new(RefCell::new(Group {
-                cells: cells
-                    .iter()
-                    .filter(|c| c.borrow().pos().x() == *x)
-                    .map(|c| c.clone())
+            Group::new(
+                GroupKind::Column,
+                (0..side_size)
+                    .map(|y| cell::Position::new(x, y).index(side_size))
                     .collect(),
-                answer_candidate: setting.answer_candidate().clone(),
-            }))
+                setting,
+            )
         })
         .collect()
 }
 
-fn create_horizontal_groups(
-    cells: &Vec<Rc<RefCell<cell::Cell>>>,
-    setting: &setting::GameSetting,
-) -> Vec<Rc<RefCell<Group>>> {
-    let y_pos: Vec<u8> = (0..setting.side_size()).collect();
-    y_pos
-        .iter()
+fn create_horizontal_groups(setting: &setting::GameSetting) -> Vec<Group> {
+    let side_size = setting.side_size();
+    (0..side_size)
         .map(|y| {
-            Rc::new(RefCell::new(Group {
-                cells: cells
-                    .iter()
-                    .filter(|c| c.borrow().pos().y() == *y)
-                    .map(|c| c.clone())
+            Group::new(
+                GroupKind::Row,
+                (0..side_size)
+                    .map(|x| cell::Position::new(x, y).index(side_size))
                     .collect(),
-                answer_candidate: setting.answer_candidate().clone(),
-            }))
+                setting,
+            )
         })
         .collect()
 }
 
-fn create_block_groups(
-    cells: &Vec<Rc<RefCell<cell::Cell>>>,
-    setting: &setting::GameSetting,
-) -> Vec<Rc<RefCell<Group>>> {
+/// Create the groups of the main diagonal from the top left and the anti-diagonal from the top right.
+///
+/// 左上からの対角線と右上からの対角線のグループを作成する。
+fn create_diagonal_groups(setting: &setting::GameSetting) -> Vec<Group> {
+    let side_size = setting.side_size();
+    let main = (0..side_size)
+        .map(|i| cell::Position::new(i, i).index(side_size))
+        .collect();
+    let anti = (0..side_size)
+        .map(|i| cell::Position::new(side_size - 1 - i, i).index(side_size))
+        .collect();
+    vec![
+        Group::new(GroupKind::Diagonal, main, setting),
+        Group::new(GroupKind::Diagonal, anti, setting),
+    ]
+}
+
+/// The windows are the size of a block, one cell apart from the edges and from each other.
+fn create_window_groups(setting: &setting::GameSetting) -> Vec<Group> {
+    let side_size = setting.side_size();
+    let height = setting.block_height();
+    let width = setting.block_width();
+    let starts = |size: u8| (0..(side_size - 1) / (size + 1)).map(move |i| 1 + i * (size + 1));
+    let mut vec: Vec<Group> = vec![];
+    for start_y in starts(height) {
+        for start_x in starts(width) {
+            let cells = (start_y..start_y + height)
+                .flat_map(|y| (start_x..start_x + width).map(move |x| (x, y)))
+                .map(|(x, y)| cell::Position::new(x, y).index(side_size))
+                .collect();
+            vec.push(Group::new(GroupKind::Window, cells, setting));
+        }
+    }
+    vec
+}
+
+fn create_block_groups(setting: &setting::GameSetting) -> Vec<Group> {
+    if let Some(regions) = setting.regions() {
+        return create_region_groups(setting, regions);
+    }
     let block_start_positions = create_block_start_positions(setting);
-    let mut vec: Vec<Rc<RefCell<Group>>> = vec![];
+    let mut vec: Vec<Group> = vec![];
     for start_pos in block_start_positions {
-        let mut one_group_cells: Vec<Rc<RefCell<cell::Cell>>> = vec![];
+        let mut one_group_cells: Vec<usize> = vec![];
         for y in 0..setting.block_height() {
             for x in 0..setting.block_width() {
                 let pos = start_pos.move_y(y as i16).move_x(x as i16);
-                one_group_cells.push(
-                    cells
-                        .iter()
-                        .find(|c| c.borrow().pos() == pos)
-                        .unwrap()
-                        .clone(),
-                );
+                one_group_cells.push(pos.index(setting.side_size()));
             }
         }
-        vec.push(Rc::new(RefCell::new(Group {
-            cells: one_group_cells,
-            answer_candidate: setting.answer_candidate().clone(),
-        })))
+        vec.push(Group::new(GroupKind::Block, one_group_cells, setting))
     }
-    return vec;
+    vec
+}
+
+/// Create the groups of the irregular regions in ascending order of the region ID.
+///
+/// 不規則な領域のグループを領域の ID の昇順に作成する。
+fn create_region_groups(setting: &setting::GameSetting, regions: &[usize]) -> Vec<Group> {
+    let mut ids: Vec<usize> = regions.to_vec();
+    ids.sort_unstable();
+    ids.dedup();
+    ids.iter()
+        .map(|id| {
+            let cells = (0..regions.len()).filter(|i| regions[*i] == *id).collect();
+            Group::new(GroupKind::Block, cells, setting)
+        })
+        .collect()
 }
 
 fn create_block_start_positions(setting: &setting::GameSetting) -> Vec<cell::Position> {
@@ -219,49 +306,151 @@ mod tests {
             width: 3,
         })
     }
+    fn pos(index: usize) -> cell::Position {
+        cell::Position::from_index(index, setting().side_size())
+    }
     #[test]
     fn test_create_vertical_groups() {
-        let vg = create_vertical_groups(&cell::create_cells(&setting()), &setting());
+        let vg = create_vertical_groups(&setting());
         assert_eq!(vg.len(), 6);
-        assert_eq!(vg[0].borrow().cells.len(), 6);
-        assert_eq!(
-            vg[0].borrow().cells.get(0).unwrap().borrow().pos(),
-            cell::Position::new(0, 0)
-        );
-        assert_eq!(
-            vg[0].borrow().cells.get(5).unwrap().borrow().pos(),
-            cell::Position::new(0, 5)
-        );
-        assert_eq!(
-            vg[5].borrow().cells.get(0).unwrap().borrow().pos(),
-            cell::Position::new(5, 0)
-        );
-        assert_eq!(
-            vg[5].borrow().cells.get(5).unwrap().borrow().pos(),
-            cell::Position::new(5, 5)
-        );
+        assert_eq!(vg[0].cells.len(), 6);
+        assert_eq!(pos(vg[0].cells[0]), cell::Position::new(0, 0));
+        assert_eq!(pos(vg[0].cells[5]), cell::Position::new(0, 5));
+        assert_eq!(pos(vg[5].cells[0]), cell::Position::new(5, 0));
+        assert_eq!(pos(vg[5].cells[5]), cell::Position::new(5, 5));
     }
     #[test]
     fn test_create_horizontal_groups() {
-        let hg = create_horizontal_groups(&cell::create_cells(&setting()), &setting());
+        let hg = create_horizontal_groups(&setting());
         assert_eq!(hg.len(), 6);
-        assert_eq!(hg[0].borrow().cells.len(), 6);
-        assert_eq!(
-            hg[0].borrow().cells.get(0).unwrap().borrow().pos(),
-            cell::Position::new(0, 0)
-        );
-        assert_eq!(
-            hg[0].borrow().cells.get(5).unwrap().borrow().pos(),
-            cell::Position::new(5, 0)
-        );
-        assert_eq!(
-            hg[5].borrow().cells.get(0).unwrap().borrow().pos(),
-            cell::Position::new(0, 5)
-        );
-        assert_eq!(
-            hg[5].borrow().cells.get(5).unwrap().borrow().pos(),
-            cell::Position::new(5, 5)
-        );
+        assert_eq!(hg[0].cells.len(), 6);
+        assert_eq!(pos(hg[0].cells[0]), cell::Position::new(0, 0));
+        assert_eq!(pos(hg[0].cells[5]), cell::Position::new(5, 0));
+        assert_eq!(pos(hg[5].cells[0]), cell::Position::new(0, 5));
+        assert_eq!(pos(hg[5].cells[5]), cell::Position::new(5, 5));
+    }
+    #[test]
+    fn test_create_peers() {
+        let groups = create_groups(&setting());
+        let cell_groups = create_cell_groups(&groups, 36);
+        let peers = create_peers(&groups, &cell_groups);
+        assert_eq!(cell_groups[0], vec![0, 6, 12]);
+        assert_eq!(peers[0].len(), 5 + 5 + 2);
+        assert!(!peers[0].contains(&0));
+        assert!(peers[0].contains(&(4 * 6)));
+        assert!(peers[0].contains(&(6 + 2)));
+    }
+    mod test_create_diagonal_groups {
+        use super::*;
+        #[test]
+        fn it_creates_no_diagonal_groups_by_default() {
+            assert_eq!(create_groups(&setting()).len(), 6 * 3);
+        }
+        #[test]
+        fn it_creates_two_diagonal_groups() {
+            let groups = create_groups(&setting().with_diagonals());
+            assert_eq!(groups.len(), 6 * 3 + 2);
+            assert!(groups[18..].iter().all(|g| g.kind() == GroupKind::Diagonal));
+            assert_eq!(
+                groups[18].cells.iter().map(|c| pos(*c)).collect::<Vec<_>>(),
+                (0..6)
+                    .map(|i| cell::Position::new(i, i))
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(
+                groups[19].cells.iter().map(|c| pos(*c)).collect::<Vec<_>>(),
+                (0..6)
+                    .map(|i| cell::Position::new(5 - i, i))
+                    .collect::<Vec<_>>()
+            );
+        }
+        #[test]
+        fn diagonal_cells_have_more_peers() {
+            let groups = create_groups(&setting().with_diagonals());
+            let cell_groups = create_cell_groups(&groups, 36);
+            let peers = create_peers(&groups, &cell_groups);
+            assert_eq!(cell_groups[0], vec![0, 6, 12, 18]);
+            assert_eq!(peers[0].len(), 5 + 5 + 2 + 4);
+            assert!(peers[0].contains(&(5 * 6 + 5)));
+            assert_eq!(peers[1].len(), 5 + 5 + 2);
+        }
+    }
+    mod test_create_window_groups {
+        use super::*;
+        fn setting_3_3() -> setting::GameSetting {
+            setting::GameSetting::new(setting::BlockSize {
+                height: 3,
+                width: 3,
+            })
+            .with_windows()
+        }
+        #[test]
+        fn it_creates_four_windows_in_9x9() {
+            let groups = create_groups(&setting_3_3());
+            assert_eq!(groups.len(), 27 + 4);
+            assert!(groups[27..].iter().all(|g| g.kind() == GroupKind::Window));
+            let to_positions = |g: &Group| {
+                g.cells()
+                    .iter()
+                    .map(|c| cell::Position::from_index(*c, 9))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(
+                to_positions(&groups[27]),
+                (1..4)
+                    .flat_map(|y| (1..4).map(move |x| cell::Position::new(x, y)))
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(
+                to_positions(&groups[30]),
+                (5..8)
+                    .flat_map(|y| (5..8).map(move |x| cell::Position::new(x, y)))
+                    .collect::<Vec<_>>()
+            );
+        }
+        #[test]
+        fn it_creates_the_windows_that_fit_in_the_board() {
+            let groups = create_window_groups(&setting().with_windows());
+            assert_eq!(groups.len(), 1);
+            assert_eq!(groups[0].cells(), &[7, 8, 9, 13, 14, 15]);
+            let groups = create_window_groups(&setting::GameSetting::new(setting::BlockSize {
+                height: 4,
+                width: 4,
+            }));
+            assert_eq!(groups.len(), 9);
+            let groups = create_window_groups(&setting::GameSetting::new(setting::BlockSize {
+                height: 3,
+                width: 1,
+            }));
+            assert!(groups.is_empty());
+        }
+        #[test]
+        fn the_cells_in_the_windows_belong_to_four_groups() {
+            let groups = create_groups(&setting_3_3());
+            let cell_groups = create_cell_groups(&groups, 81);
+            assert_eq!(cell_groups[2 * 9 + 2], vec![2, 11, 18, 27]);
+            assert_eq!(cell_groups[4 * 9 + 4], vec![4, 13, 22]);
+        }
+    }
+    mod test_create_region_groups {
+        use super::*;
+        #[test]
+        fn it_creates_the_blocks_from_the_regions() {
+            let regions = vec![
+                0, 0, 0, 1, 1, 1, //
+                0, 0, 2, 1, 1, 1, //
+                0, 2, 2, 2, 3, 3, //
+                4, 2, 2, 3, 3, 3, //
+                4, 4, 4, 3, 5, 5, //
+                4, 4, 5, 5, 5, 5,
+            ];
+            let setting = setting().with_regions(regions).unwrap();
+            let groups = create_block_groups(&setting);
+            assert_eq!(groups.len(), 6);
+            assert!(groups.iter().all(|g| g.kind() == GroupKind::Block));
+            assert_eq!(groups[0].cells(), &[0, 1, 2, 6, 7, 12]);
+            assert_eq!(groups[5].cells(), &[28, 29, 32, 33, 34, 35]);
+        }
     }
     mod test_create_block_groups {
         use super::*;
@@ -282,23 +471,22 @@ mod tests {
         }
         #[test]
         fn block_group_count() {
-            let groups = create_block_groups(&cell::create_cells(&setting()), &setting());
+            let groups = create_block_groups(&setting());
             assert_eq!(groups.len(), 6);
         }
         #[test]
         fn block_group_cell_count() {
-            let groups = create_block_groups(&cell::create_cells(&setting()), &setting());
-            assert!(groups.iter().all(|g| g.borrow().cells.len() == 6));
+            let groups = create_block_groups(&setting());
+            assert!(groups.iter().all(|g| g.cells.len() == 6));
         }
         #[test]
         fn first_block_group_cells() {
-            let groups = create_block_groups(&cell::create_cells(&setting()), &setting());
+            let groups = create_block_groups(&setting());
             assert_eq!(
                 groups[0]
-                    .borrow()
                     .cells
                     .iter()
-                    .map(|c| c.borrow().pos())
+                    .map(|c| pos(*c))
                     .collect::<Vec<cell::Position>>(),
                 vec![
                     cell::Position::new(0, 0),
@@ -312,13 +500,12 @@ mod tests {
         }
         #[test]
         fn last_block_group_cells() {
-            let groups = create_block_groups(&cell::create_cells(&setting()), &setting());
+            let groups = create_block_groups(&setting());
             assert_eq!(
                 groups[5]
-                    .borrow()
                     .cells
                     .iter()
-                    .map(|c| c.borrow().pos())
+                    .map(|c| pos(*c))
                     .collect::<Vec<cell::Position>>(),
                 vec![
                     cell::Position::new(3, 4),
@@ -333,23 +520,24 @@ mod tests {
     }
     #[test]
     fn get_lonely_returns_lonely() {
-        let g = create_horizontal_groups(&cell::create_cells(&setting()), &setting());
+        let mut cells = cell::create_cells(&setting());
+        let g = create_horizontal_groups(&setting());
         // index が 2 以外の cell の解答候補から 3 を除去
-        for (i, cell) in g[0].borrow().cells.iter().enumerate() {
+        for (i, c) in g[0].cells.iter().enumerate() {
             if i == 2 {
                 continue;
             };
-            cell.borrow_mut().remove_answer_candidate(3);
+            cells[*c].remove_answer_candidate(3);
         }
         // index が 4 以外の cell の解答候補から 5 を除去
-        for (i, cell) in g[0].borrow().cells.iter().enumerate() {
+        for (i, c) in g[0].cells.iter().enumerate() {
             if i == 4 {
                 continue;
             };
-            cell.borrow_mut().remove_answer_candidate(5);
+            cells[*c].remove_answer_candidate(5);
         }
         assert_eq!(
-            g[0].borrow().get_lonely(),
+            g[0].get_lonely(&cells),
             vec![
                 (cell::Position::new(2, 0), 3),
                 (cell::Position::new(4, 0), 5)
@@ -360,42 +548,40 @@ mod tests {
         use super::*;
         #[test]
         fn duplicated() {
-            let g = create_horizontal_groups(&cell::create_cells(&setting()), &setting());
-            let g = g[0].borrow_mut();
-            let cells = g.cells();
-            cells[0].borrow_mut().set_answer(1);
-            cells[1].borrow_mut().set_answer(1);
-            cells[2].borrow_mut().set_answer(3);
-            cells[3].borrow_mut().set_answer(4);
-            cells[4].borrow_mut().set_answer(5);
-            cells[5].borrow_mut().set_answer(6);
-            assert!(g.is_duplicate_answer());
+            let mut cells = cell::create_cells(&setting());
+            let g = &create_horizontal_groups(&setting())[0];
+            cells[g.cells[0]].set_answer(1);
+            cells[g.cells[1]].set_answer(1);
+            cells[g.cells[2]].set_answer(3);
+            cells[g.cells[3]].set_answer(4);
+            cells[g.cells[4]].set_answer(5);
+            cells[g.cells[5]].set_answer(6);
+            assert!(g.is_duplicate_answer(&cells));
         }
         #[test]
         fn not_duplicated() {
-            let g = create_horizontal_groups(&cell::create_cells(&setting()), &setting());
-            let g = g[0].borrow_mut();
-            let cells = g.cells();
-            cells[0].borrow_mut().set_answer(1);
-            cells[1].borrow_mut().set_answer(2);
-            cells[2].borrow_mut().set_answer(3);
-            cells[3].borrow_mut().set_answer(4);
-            cells[4].borrow_mut().set_answer(5);
-            cells[5].borrow_mut().set_answer(6);
-            assert!(!g.is_duplicate_answer());
+            let mut cells = cell::create_cells(&setting());
+            let g = &create_horizontal_groups(&setting())[0];
+            cells[g.cells[0]].set_answer(1);
+            cells[g.cells[1]].set_answer(2);
+            cells[g.cells[2]].set_answer(3);
+            cells[g.cells[3]].set_answer(4);
+            cells[g.cells[4]].set_answer(5);
+            cells[g.cells[5]].set_answer(6);
+            assert!(!g.is_duplicate_answer(&cells));
         }
     }
     mod restore_answer_candidate {
         use super::*;
         #[test]
         fn it_added_to_answer_candidate() {
-            let g = create_horizontal_groups(&cell::create_cells(&setting()), &setting());
-            let mut g = g[0].borrow_mut();
+            let cells = cell::create_cells(&setting());
+            let mut g = create_horizontal_groups(&setting()).remove(0);
             g.remove_answer_candidate(1);
             g.remove_answer_candidate(2);
             g.remove_answer_candidate(3);
-            g.restore_answer_candidate(2);
-            assert_eq!(g.answer_candidate, [4, 5, 6, 2]);
+            g.restore_answer_candidate(2, &cells);
+            assert_eq!(g.answer_candidate(), [2, 4, 5, 6]);
         }
     }
 }
@@ -0,0 +1,124 @@
+use crate::normal_game::cell::Position;
+use crate::normal_game::technique::Step;
+use crate::normal_game::technique::Technique;
+use crate::normal_game::NormalGame;
+
+/// The next move to tell the player.
+/// `targets` are the cells whose answer is set or whose answer_candidate are removed.
+/// `cells` and `groups` are the cells and the indexes of the groups that support the deduction.
+///
+/// プレイヤーに伝える次の一手。
+/// `targets` は解答を設定する、または answer_candidate を除去するセルである。
+/// `cells` と `groups` は推論の根拠となるセルとグループのインデックスである。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hint {
+    pub technique: Technique,
+    pub name: &'static str,
+    pub targets: Vec<Position>,
+    pub placements: Vec<(Position, u8)>,
+    pub eliminations: Vec<(Position, u8)>,
+    pub cells: Vec<Position>,
+    pub digits: Vec<u8>,
+    pub groups: Vec<usize>,
+    pub chain: Vec<(Position, u8)>,
+}
+
+impl From<Step> for Hint {
+    fn from(step: Step) -> Hint {
+        let mut targets: Vec<Position> = vec![];
+        for (pos, _) in step.placements.iter().chain(step.eliminations.iter()) {
+            if !targets.contains(pos) {
+                targets.push(*pos);
+            }
+        }
+        Hint {
+            technique: step.technique,
+            name: step.technique.name(),
+            targets,
+            placements: step.placements,
+            eliminations: step.eliminations,
+            cells: step.cells,
+            digits: step.digits,
+            groups: step.groups,
+            chain: step.chain,
+        }
+    }
+}
+
+impl NormalGame {
+    /// Returns the easiest deduction that can be made next, without changing the game.
+    /// Returns None if no technique can be applied.
+    ///
+    /// ゲームを変更せずに、次に行える最も易しい推論を返却する。
+    /// 適用できるテクニックがない場合は None を返却する。
+    pub fn next_hint(&self) -> Option<Hint> {
+        self.next_hint_with(&Technique::ALL)
+    }
+
+    /// Returns the easiest deduction that can be made next with the specified techniques, without changing the game.
+    ///
+    /// 指定したテクニックで次に行える最も易しい推論を、ゲームを変更せずに返却する。
+    pub fn next_hint_with(&self, techniques: &[Technique]) -> Option<Hint> {
+        let mut techniques = techniques.to_vec();
+        techniques.sort();
+        self.find_step(&techniques).map(Hint::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normal_game::setting::BlockSize;
+    use crate::normal_game::setting::GameSetting;
+    fn game(issue: &str) -> NormalGame {
+        let mut game = NormalGame::new(GameSetting::new(BlockSize {
+            height: 3,
+            width: 3,
+        }));
+        game.load(issue);
+        game
+    }
+    #[test]
+    fn it_does_not_change_the_game() {
+        let game = game(" 7     6 |6   1   3|  54 87  |  8   4  | 1  3  5 |  9   1  |  35 12  |7   2   8| 5     9 ");
+        let before = game.clone();
+        game.next_hint().unwrap();
+        assert!(game == before);
+        assert_eq!(game.to_string(), before.to_string());
+    }
+    #[test]
+    fn it_returns_the_easiest_hint() {
+        let game = game(" 7     6 |6   1   3|  54 87  |  8   4  | 1  3  5 |  9   1  |  35 12  |7   2   8| 5     9 ");
+        let hint = game.next_hint().unwrap();
+        let solved = game.solve().unwrap();
+        assert_eq!(hint.technique, Technique::HiddenSingle);
+        assert_eq!(hint.name, "Hidden Single");
+        assert_eq!(hint.placements.len(), 1);
+        let (pos, answer) = hint.placements[0];
+        assert_eq!(hint.targets, vec![pos]);
+        assert_eq!(hint.digits, vec![answer]);
+        assert_eq!(solved.find_cell(pos).unwrap().answer(), Some(answer));
+        assert_eq!(hint.groups.len(), 1);
+        assert!(game.groups()[hint.groups[0]]
+            .cells()
+            .contains(&game.index_of(pos).unwrap()));
+    }
+    #[test]
+    fn it_returns_the_eliminations() {
+        let game = game("   2  861|  5  1   |      4  |8  47    | 2 18 3  | 34      |  7      |9  5    2| 8 6  1   ");
+        let hint = game
+            .next_hint_with(&[Technique::Pointing, Technique::Claiming])
+            .unwrap();
+        assert!(hint.placements.is_empty());
+        assert!(!hint.eliminations.is_empty());
+        assert!(hint
+            .eliminations
+            .iter()
+            .all(|(pos, _)| hint.targets.contains(pos)));
+    }
+    #[test]
+    fn it_returns_none_for_solved_game() {
+        let game = game("174392865|682715943|935468721|528176439|417839652|369254187|893541276|746923518|251687394");
+        assert!(game.next_hint().is_none());
+    }
+}
@@ -0,0 +1,319 @@
+use crate::normal_game::cell::Position;
+use crate::normal_game::NormalGame;
+use std::fmt;
+
+/// The reason why an issue string could not be loaded.
+///
+/// 問題文字列を読み込めなかった理由。
+#[derive(Debug, PartialEq, Clone)]
+pub enum LoadError {
+    /// The token at the position is neither blank nor a number.
+    InvalidToken { pos: Position, token: String },
+    /// The number at the position is not one of the answer_candidate of the GameSetting.
+    OutOfRange { pos: Position, answer: u8 },
+    /// The number of rows does not match the side size.
+    WrongRowCount { expected: u8, actual: usize },
+    /// The number of cells in the row `y` does not match the side size.
+    WrongRowLength { y: u8, expected: u8, actual: usize },
+    /// Some givens have the same answer as another cell in the same group.
+    Contradiction(Vec<ConflictingGiven>),
+}
+
+/// A given that has the same answer as other cells in the groups it belongs to.
+///
+/// 所属するグループ内の他のセルと同じ解答を持つ初期値。
+#[derive(Debug, PartialEq, Clone)]
+pub struct ConflictingGiven {
+    pub pos: Position,
+    pub answer: u8,
+    pub peers: Vec<Position>,
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::InvalidToken { pos, token } => write!(
+                f,
+                "invalid token {:?} at row {}, column {}",
+                token,
+                pos.y(),
+                pos.x()
+            ),
+            LoadError::OutOfRange { pos, answer } => write!(
+                f,
+                "answer {} at row {}, column {} is out of range",
+                answer,
+                pos.y(),
+                pos.x()
+            ),
+            LoadError::WrongRowCount { expected, actual } => {
+                write!(f, "expected {} rows, but found {}", expected, actual)
+            }
+            LoadError::WrongRowLength {
+                y,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "expected {} cells in row {}, but found {}",
+                expected, y, actual
+            ),
+            LoadError::Contradiction(conflicts) => {
+                write!(f, "contradictory givens at")?;
+                for conflict in conflicts {
+                    write!(
+                        f,
+                        " (row {}, column {})",
+                        conflict.pos.y(),
+                        conflict.pos.x()
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl NormalGame {
+    /// ' 7     6 |6   1   3|  54 87  |  8   4  | 1  3  5 |  9   1  |  35 12  |7   2   8| 5     9 '
+    pub fn load(&mut self, issue: &str) {
+        for (y, answers) in split_issue(issue).iter().enumerate() {
+            for (x, answer) in answers.iter().enumerate() {
+                if is_blank(answer) {
+                    continue;
+                }
+                let answer: u8 = answer.parse().expect("issue is wrong.");
+                self.set_answer(Position::new(x as u8, y as u8), answer);
+            }
+        }
+    }
+
+    /// Load the issue like `load`, but return an error instead of panicking.
+    /// Unlike `load`, the number of rows and the length of each row must match the side size.
+    /// If an error is returned, the game is not changed.
+    ///
+    /// `load` と同様に問題を読み込むが、panic する代わりにエラーを返却する。
+    /// `load` と異なり、行数と各行の長さは一辺のサイズと一致している必要がある。
+    /// エラーを返却した場合、ゲームは変更されない。
+    pub fn try_load(&mut self, issue: &str) -> Result<(), LoadError> {
+        for (pos, answer) in self.parse_issue(issue)? {
+            self.set_answer(pos, answer);
+        }
+        Ok(())
+    }
+
+    /// Load the issue like `try_load`, and also reject givens that contradict each other.
+    /// All the contradictory givens are reported at once.
+    ///
+    /// `try_load` と同様に問題を読み込み、互いに矛盾する初期値も拒否する。
+    /// 矛盾する初期値は全てまとめて報告する。
+    pub fn try_load_strict(&mut self, issue: &str) -> Result<(), LoadError> {
+        let givens = self.parse_issue(issue)?;
+        let mut game = self.clone();
+        for (pos, answer) in givens.iter() {
+            game.set_answer(*pos, *answer);
+        }
+        let conflicts: Vec<ConflictingGiven> = givens
+            .iter()
+            .map(|(pos, answer)| ConflictingGiven {
+                pos: *pos,
+                answer: *answer,
+                peers: game.conflicting_peers(pos.index(game.setting().side_size()), *answer),
+            })
+            .filter(|conflict| !conflict.peers.is_empty())
+            .collect();
+        if !conflicts.is_empty() {
+            return Err(LoadError::Contradiction(conflicts));
+        }
+        *self = game;
+        Ok(())
+    }
+
+    /// Parse the issue into the positions and answers of the given cells.
+    fn parse_issue(&self, issue: &str) -> Result<Vec<(Position, u8)>, LoadError> {
+        let side_size = self.setting().side_size();
+        let answer_candidate = self.setting().answer_candidate();
+        let rows = split_issue(issue);
+        if rows.len() != side_size as usize {
+            return Err(LoadError::WrongRowCount {
+                expected: side_size,
+                actual: rows.len(),
+            });
+        }
+        let mut givens: Vec<(Position, u8)> = vec![];
+        for (y, answers) in rows.iter().enumerate() {
+            if answers.len() != side_size as usize {
+                return Err(LoadError::WrongRowLength {
+                    y: y as u8,
+                    expected: side_size,
+                    actual: answers.len(),
+                });
+            }
+            for (x, answer) in answers.iter().enumerate() {
+                if is_blank(answer) {
+                    continue;
+                }
+                let pos = Position::new(x as u8, y as u8);
+                let answer: u8 = answer.parse().map_err(|_| LoadError::InvalidToken {
+                    pos,
+                    token: answer.clone(),
+                })?;
+                if !answer_candidate.contains(&answer) {
+                    return Err(LoadError::OutOfRange { pos, answer });
+                }
+                givens.push((pos, answer));
+            }
+        }
+        Ok(givens)
+    }
+}
+
+/// Split the issue into rows of answer tokens.
+/// Rows are separated by `|`, and if a row contains `,`, it is separated by `,`, otherwise it is separated by each character.
+fn split_issue(issue: &str) -> Vec<Vec<String>> {
+    issue
+        .split('|')
+        .map(|horizontal_line| {
+            if horizontal_line.contains(',') {
+                horizontal_line.split(',').map(|s| s.to_string()).collect()
+            } else {
+                horizontal_line.chars().map(|c| c.to_string()).collect()
+            }
+        })
+        .collect()
+}
+
+fn is_blank(answer: &str) -> bool {
+    answer == " " || answer.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normal_game::setting::BlockSize;
+    use crate::normal_game::setting::GameSetting;
+    fn game() -> NormalGame {
+        NormalGame::new(GameSetting::new(BlockSize {
+            height: 2,
+            width: 2,
+        }))
+    }
+    mod try_load {
+        use super::*;
+        #[test]
+        fn it_loads_valid_issue() {
+            let mut game = game();
+            assert_eq!(game.try_load("1 3 | 4 2|    |4  1"), Ok(()));
+            assert_eq!(game.to_string(), "1 3 | 4 2|    |4  1");
+        }
+        #[test]
+        fn it_loads_valid_issue_with_comma() {
+            let mut game = game();
+            assert_eq!(game.try_load("1, ,3, | ,4, ,2| , , , |4, , ,1"), Ok(()));
+            assert_eq!(game.to_string(), "1 3 | 4 2|    |4  1");
+        }
+        #[test]
+        fn it_reports_invalid_token() {
+            let mut game = game();
+            assert_eq!(
+                game.try_load("1 3 | 4 2|  x |4  1"),
+                Err(LoadError::InvalidToken {
+                    pos: Position::new(2, 2),
+                    token: "x".to_string()
+                })
+            );
+        }
+        #[test]
+        fn it_reports_out_of_range_answer() {
+            let mut game = game();
+            assert_eq!(
+                game.try_load("1 3 | 4 2|    |4  5"),
+                Err(LoadError::OutOfRange {
+                    pos: Position::new(3, 3),
+                    answer: 5
+                })
+            );
+        }
+        #[test]
+        fn it_reports_wrong_row_count() {
+            let mut game = game();
+            assert_eq!(
+                game.try_load("1 3 | 4 2|    "),
+                Err(LoadError::WrongRowCount {
+                    expected: 4,
+                    actual: 3
+                })
+            );
+        }
+        #[test]
+        fn it_reports_wrong_row_length() {
+            let mut game = game();
+            assert_eq!(
+                game.try_load("1 3 | 4 2|  |4  1"),
+                Err(LoadError::WrongRowLength {
+                    y: 2,
+                    expected: 4,
+                    actual: 2
+                })
+            );
+        }
+        #[test]
+        fn it_does_not_change_the_game_on_error() {
+            let mut game = game();
+            assert!(game.try_load("1 3 | 4 2|    |4  x").is_err());
+            assert_eq!(game.answered_count(), 0);
+        }
+    }
+    mod try_load_strict {
+        use super::*;
+        #[test]
+        fn it_loads_valid_issue() {
+            let mut game = game();
+            assert_eq!(game.try_load_strict("1 3 | 4 2|    |4  1"), Ok(()));
+            assert_eq!(game.to_string(), "1 3 | 4 2|    |4  1");
+        }
+        #[test]
+        fn it_reports_all_contradictory_givens() {
+            let mut game = game();
+            assert_eq!(
+                game.try_load_strict("1 3 | 4 2| 4  |4 41"),
+                Err(LoadError::Contradiction(vec![
+                    ConflictingGiven {
+                        pos: Position::new(1, 1),
+                        answer: 4,
+                        peers: vec![Position::new(1, 2)]
+                    },
+                    ConflictingGiven {
+                        pos: Position::new(1, 2),
+                        answer: 4,
+                        peers: vec![Position::new(1, 1), Position::new(0, 3)]
+                    },
+                    ConflictingGiven {
+                        pos: Position::new(0, 3),
+                        answer: 4,
+                        peers: vec![Position::new(2, 3), Position::new(1, 2)]
+                    },
+                    ConflictingGiven {
+                        pos: Position::new(2, 3),
+                        answer: 4,
+                        peers: vec![Position::new(0, 3)]
+                    },
+                ]))
+            );
+            assert_eq!(game.answered_count(), 0);
+        }
+        #[test]
+        fn it_reports_parse_error_first() {
+            let mut game = game();
+            assert_eq!(
+                game.try_load_strict("1 3 | 4 2|    |4 4x"),
+                Err(LoadError::InvalidToken {
+                    pos: Position::new(3, 3),
+                    token: "x".to_string()
+                })
+            );
+        }
+    }
+}
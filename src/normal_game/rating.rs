@@ -0,0 +1,151 @@
+use crate::normal_game::difficulty::Difficulty;
+use crate::normal_game::strategy::SolveStrategy;
+use crate::normal_game::technique::Technique;
+use crate::normal_game::NormalGame;
+
+/// The score of a game that cannot be solved without guessing.
+/// It is higher than the score of any technique.
+///
+/// 推測をしなければ解けないゲームのスコア。どのテクニックのスコアよりも高い。
+pub const GUESS_SCORE: f32 = 11.0;
+
+/// The rating of a game.
+/// `score` is the score of the hardest technique required, on a scale similar to the Sudoku Explainer rating,
+/// and `steps` is the number of steps taken to solve the game with the techniques.
+/// If the game cannot be solved without guessing, `hardest` is None and `score` is `GUESS_SCORE`.
+///
+/// ゲームのレーティング。
+/// `score` は必要となる最も難しいテクニックの Sudoku Explainer のレーティングに近い尺度のスコアで、
+/// `steps` はテクニックでゲームを解くのにかかったステップ数である。
+/// 推測をしなければ解けないゲームの場合、`hardest` は None で `score` は `GUESS_SCORE` となる。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rating {
+    pub score: f32,
+    pub hardest: Option<Technique>,
+    pub difficulty: Difficulty,
+    pub steps: usize,
+}
+
+impl NormalGame {
+    /// Rate the game by solving it with the techniques as a human would.
+    /// It assumes that the game has only one solution.
+    /// Like `SolveStrategy::Logical`, it does not use trial and error,
+    /// so a game that cannot be solved without it is rated as requiring guessing.
+    ///
+    /// 人間と同じようにテクニックでゲームを解き、レーティングする。
+    /// ゲームの解答がただ 1 つであることを前提とする。
+    /// `SolveStrategy::Logical` と同様に試行錯誤は使用しないため、試行錯誤なしでは解けないゲームは推測が必要なものとしてレーティングする。
+    pub fn rate(&self) -> Rating {
+        self.rate_with(&SolveStrategy::logical_techniques())
+    }
+
+    /// Rate the game like `rate`, but also use the techniques that assume the game has only one solution,
+    /// such as Unique Rectangles. Use it only for the games whose solution is known to be unique.
+    ///
+    /// `rate` と同様にゲームをレーティングするが、Unique Rectangle などのゲームの解答がただ 1 つであることを前提とするテクニックも使用する。
+    /// 解答がただ 1 つであることが分かっているゲームにのみ使用すること。
+    pub fn rate_assuming_unique_solution(&self) -> Rating {
+        let techniques: Vec<Technique> = Technique::all_with_uniqueness()
+            .into_iter()
+            .filter(|t| *t != Technique::TrialAndError)
+            .collect();
+        self.rate_with(&techniques)
+    }
+
+    /// Rate the game using only the specified techniques.
+    ///
+    /// 指定したテクニックのみを使用してゲームをレーティングする。
+    pub fn rate_with(&self, techniques: &[Technique]) -> Rating {
+        let solution = self.solve_logically(techniques);
+        let steps = solution.steps.len();
+        if !solution.is_solved() {
+            return Rating {
+                score: GUESS_SCORE,
+                hardest: None,
+                difficulty: Difficulty::Expert,
+                steps,
+            };
+        }
+        let hardest = solution.hardest();
+        Rating {
+            score: hardest.map(|t| t.score()).unwrap_or(0.0),
+            hardest,
+            difficulty: solution
+                .steps
+                .iter()
+                .map(|s| s.technique.difficulty())
+                .max()
+                .unwrap_or(Difficulty::Easy),
+            steps,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normal_game::setting::BlockSize;
+    use crate::normal_game::setting::GameSetting;
+    fn game(issue: &str) -> NormalGame {
+        let mut game = NormalGame::new(GameSetting::new(BlockSize {
+            height: 3,
+            width: 3,
+        }));
+        game.load(issue);
+        game
+    }
+    #[test]
+    fn solved_game_has_no_score() {
+        let rating = game("174392865|682715943|935468721|528176439|417839652|369254187|893541276|746923518|251687394").rate();
+        assert_eq!(rating.score, 0.0);
+        assert_eq!(rating.hardest, None);
+        assert_eq!(rating.difficulty, Difficulty::Easy);
+        assert_eq!(rating.steps, 0);
+    }
+    #[test]
+    fn it_rates_with_the_hardest_technique() {
+        let rating = game(" 7     6 |6   1   3|  54 87  |  8   4  | 1  3  5 |  9   1  |  35 12  |7   2   8| 5     9 ").rate();
+        let hardest = rating.hardest.unwrap();
+        assert_eq!(rating.score, hardest.score());
+        assert_eq!(rating.difficulty, hardest.difficulty());
+        assert_eq!(rating.steps, 81 - 25);
+    }
+    #[test]
+    fn game_that_requires_guessing_has_the_guess_score() {
+        let rating = game("1").rate_with(&[Technique::HiddenSingle]);
+        assert_eq!(rating.score, GUESS_SCORE);
+        assert_eq!(rating.hardest, None);
+        assert_eq!(rating.difficulty, Difficulty::Expert);
+    }
+    #[test]
+    fn game_that_requires_trial_and_error_is_rated_as_requiring_guessing() {
+        let game = game(" 56  1  2|    6 9  | 8     74| 4      8|6235     |    7    |4 12 6   |  8   236|     7   ");
+        assert!(game.has_unique_solution());
+        assert_eq!(
+            game.rate_with(&Technique::ALL).hardest,
+            Some(Technique::TrialAndError)
+        );
+        let rating = game.rate();
+        assert_eq!(rating.score, GUESS_SCORE);
+        assert_eq!(rating.hardest, None);
+        assert_eq!(rating.difficulty, Difficulty::Expert);
+        assert!(game.solve_with(SolveStrategy::Logical).is_none());
+    }
+    #[test]
+    fn uniqueness_techniques_are_used_only_when_declared() {
+        let game = game("   2  861|  5  1   |      4  |8  47    | 2 18 3  | 34      |  7      |9  5    2| 8 6  1   ");
+        let rating = game.rate();
+        let unique_rating = game.rate_assuming_unique_solution();
+        assert!(!rating.hardest.iter().any(|t| t.requires_unique_solution()));
+        assert!(unique_rating.score <= rating.score);
+    }
+    #[test]
+    fn harder_game_has_higher_score() {
+        let easy = game(" 7     6 |6   1   3|  54 87  |  8   4  | 1  3  5 |  9   1  |  35 12  |7   2   8| 5     9 ").rate();
+        let hard = game(
+            "5 2 9 1|   1   8|3    6  2| 4    7|6       1|  5    9|9  7    4| 6   3|  7 2 5 3",
+        )
+        .rate();
+        assert!(easy.score <= hard.score);
+    }
+}
@@ -0,0 +1,143 @@
+use crate::normal_game::cell::Cell;
+use crate::normal_game::cell::Position;
+
+/// A relation between the cells that cannot have the same answer, other than sharing a group.
+/// Unlike a group, the related cells do not have to hold every answer_candidate.
+///
+/// - AntiKnight: The cells a knight's move apart in chess.
+/// - AntiKing: The cells a king's move apart in chess, which are the diagonally adjacent cells
+///   in addition to the cells already in the same row or column.
+///
+/// グループの共有以外で、同じ解答を持てないセル同士の関係。
+/// グループと異なり、関係するセルは全ての answer_candidate を保有する必要はない。
+///
+/// - AntiKnight: チェスのナイトの動きで移動できるセル。
+/// - AntiKing: チェスのキングの動きで移動できるセル。同じ行や列のセルに加え、斜めに隣接するセルが該当する。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PeerRelation {
+    AntiKnight,
+    AntiKing,
+}
+
+impl PeerRelation {
+    fn offsets(&self) -> &'static [(i16, i16)] {
+        match self {
+            PeerRelation::AntiKnight => &[
+                (-2, -1),
+                (-1, -2),
+                (1, -2),
+                (2, -1),
+                (-2, 1),
+                (-1, 2),
+                (1, 2),
+                (2, 1),
+            ],
+            PeerRelation::AntiKing => &[
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (-1, 0),
+                (1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+            ],
+        }
+    }
+
+    /// Returns the positions of the cells related to the position inside the board.
+    ///
+    /// 指定した Position と関係する盤面内のセルの Position を返却する。
+    pub fn related(&self, pos: Position, side_size: u8) -> Vec<Position> {
+        self.offsets()
+            .iter()
+            .map(|(dx, dy)| (pos.x() as i16 + dx, pos.y() as i16 + dy))
+            .filter(|(x, y)| (0..side_size as i16).contains(x) && (0..side_size as i16).contains(y))
+            .map(|(x, y)| Position::new(x as u8, y as u8))
+            .collect()
+    }
+}
+
+/// Add the related cells to the peers, because they cannot have the same answer.
+///
+/// 関係するセルは同じ解答を持てないため、ピアに追加する。
+pub fn add_relation_peers(peers: &mut [Vec<usize>], relations: &[PeerRelation], side_size: u8) {
+    for (index, cell_peers) in peers.iter_mut().enumerate() {
+        let pos = Position::from_index(index, side_size);
+        for relation in relations.iter() {
+            for related in relation.related(pos, side_size) {
+                let related = related.index(side_size);
+                if !cell_peers.contains(&related) {
+                    cell_peers.push(related);
+                }
+            }
+        }
+    }
+}
+
+/// Returns true if any related cells have the same answer.
+///
+/// 関係するセル同士が同じ解答を持つ場合に true を返す。
+pub fn is_violated(relations: &[PeerRelation], cells: &[Cell], side_size: u8) -> bool {
+    cells.iter().filter(|c| c.answer().is_some()).any(|cell| {
+        relations.iter().any(|relation| {
+            relation
+                .related(cell.pos(), side_size)
+                .iter()
+                .any(|pos| cells[pos.index(side_size)].answer() == cell.answer())
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn pos(x: u8, y: u8) -> Position {
+        Position::new(x, y)
+    }
+    mod related {
+        use super::*;
+        #[test]
+        fn it_returns_the_knight_moves() {
+            let mut related = PeerRelation::AntiKnight.related(pos(4, 4), 9);
+            related.sort_by_key(|p| p.index(9));
+            assert_eq!(
+                related,
+                vec![
+                    pos(3, 2),
+                    pos(5, 2),
+                    pos(2, 3),
+                    pos(6, 3),
+                    pos(2, 5),
+                    pos(6, 5),
+                    pos(3, 6),
+                    pos(5, 6)
+                ]
+            );
+        }
+        #[test]
+        fn it_returns_the_moves_inside_the_board() {
+            let mut related = PeerRelation::AntiKnight.related(pos(0, 0), 9);
+            related.sort_by_key(|p| p.index(9));
+            assert_eq!(related, vec![pos(2, 1), pos(1, 2)]);
+            let mut related = PeerRelation::AntiKing.related(pos(8, 0), 9);
+            related.sort_by_key(|p| p.index(9));
+            assert_eq!(related, vec![pos(7, 0), pos(7, 1), pos(8, 1)]);
+        }
+    }
+    mod add_relation_peers {
+        use super::*;
+        #[test]
+        fn it_adds_the_related_cells_only_once() {
+            let mut peers: Vec<Vec<usize>> = vec![vec![]; 81];
+            peers[0].push(1);
+            add_relation_peers(
+                &mut peers,
+                &[PeerRelation::AntiKing, PeerRelation::AntiKnight],
+                9,
+            );
+            assert_eq!(peers[0], vec![1, 9, 10, 19, 11]);
+            assert_eq!(peers[40].len(), 16);
+        }
+    }
+}
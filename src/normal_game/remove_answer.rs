@@ -1,76 +1,66 @@
+use crate::normal_game::candidates::Candidates;
 use crate::normal_game::cell::Position;
 use crate::normal_game::group::Group;
 use crate::normal_game::NormalGame;
-use core::cell::RefCell;
-use slice_group_by::GroupBy;
-use std::rc::Rc;
 
 impl NormalGame {
     pub fn remove_answer(&mut self, pos: Position) -> Option<u8> {
-        let cell = self.find_cell(pos).unwrap();
-        let removed_answer = cell.borrow_mut().remove_answer();
-        if removed_answer.is_none() {
-            return removed_answer;
+        let index = self.index_of(pos).unwrap();
+        let removed_answer = self.cells[index].remove_answer()?;
+        for g in self.cell_groups[index].iter() {
+            self.groups[*g].restore_answer_candidate(removed_answer, &self.cells);
         }
-        let removed_answer = removed_answer.unwrap();
-        let target_groups = self.find_groups(pos);
-        target_groups
-            .iter()
-            .for_each(|g| g.borrow_mut().restore_answer_candidate(removed_answer));
-        let positions_into_target_groups: Vec<Position> = target_groups
+        // Restore the answer_candidate of the unanswered cells in the target groups.
+        // The answers of the peers outside the groups, such as in the same cage, are excluded as well.
+        let side_size = self.setting.side_size();
+        let target_cells: Vec<usize> = std::iter::once(index)
+            .chain(self.peers[index].iter().copied())
+            .filter(|c| self.cells[*c].answer().is_none())
+            .collect();
+        for c in target_cells.iter() {
+            let groups = self.find_groups(Position::from_index(*c, side_size));
+            let answer_candidate = self.find_answer_candidate_that_all_groups_hold(groups);
+            let peer_answers: Candidates = self.peers[*c]
+                .iter()
+                .filter_map(|p| self.cells[*p].answer())
+                .collect();
+            self.cells[*c].restore_answer_candidate(answer_candidate.difference(peer_answers));
+        }
+        // Then narrow them down again by the sums of the cages they belong to.
+        let mut cages: Vec<usize> = target_cells
             .iter()
-            .map(|g| {
-                let poslist: Vec<Position> = g
-                    .borrow()
-                    .cells()
-                    .iter()
-                    .map(|c| c.borrow().pos())
-                    .collect();
-                poslist
-            })
-            .flatten()
+            .flat_map(|c| self.cell_cages[*c].iter().copied())
             .collect();
-        for pos in positions_into_target_groups {
-            let groups = self.find_groups(pos);
-            self.find_cell(pos)
-                .unwrap()
-                .borrow_mut()
-                .restore_answer_candidate(&self.find_answer_candidate_that_all_groups_hold(groups));
+        cages.sort_unstable();
+        cages.dedup();
+        for c in cages {
+            self.restrict_cage(c);
         }
         self.answered_count -= 1;
         Some(removed_answer)
     }
 
-    fn find_groups(&self, pos: Position) -> Vec<&Rc<RefCell<Group>>> {
-        self.groups()
+    fn find_groups(&self, pos: Position) -> Vec<&Group> {
+        let index = self.index_of(pos).unwrap();
+        self.cell_groups[index]
             .iter()
-            .filter(|g| g.borrow().cells().iter().any(|c| c.borrow().pos() == pos))
+            .map(|g| &self.groups[*g])
             .collect()
     }
 
-    fn find_answer_candidate_that_all_groups_hold(
-        &self,
-        target_groups: Vec<&Rc<RefCell<Group>>>,
-    ) -> Vec<u8> {
-        let mut target_groups_answer_candidate: Vec<u8> = target_groups
+    fn find_answer_candidate_that_all_groups_hold(&self, target_groups: Vec<&Group>) -> Candidates {
+        target_groups
             .iter()
-            .map(|g| g.borrow().answer_candidate())
-            .flatten()
-            .collect();
-        target_groups_answer_candidate.sort();
-        let mut answer_candidate: Vec<u8> = Vec::new();
-        for array in target_groups_answer_candidate.linear_group_by(|a, b| a == b) {
-            if array.len() == target_groups.len() {
-                answer_candidate.push(array[0]);
-            }
-        }
-        answer_candidate
+            .map(|g| g.candidates())
+            .reduce(|a, b| a.intersection(b))
+            .unwrap_or_default()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::normal_game::group::GroupKind;
     use crate::normal_game::setting::BlockSize;
     use crate::normal_game::setting::GameSetting;
     use crate::normal_game::NormalGame;
@@ -89,12 +79,11 @@ mod tests {
         fn it_find_the_groups_that_contains_the_specified_positions_cell() {
             let game = NormalGame::new(setting());
             let groups = game.find_groups(Position::new(0, 0));
-            fn to_positions(group: &Rc<RefCell<Group>>) -> Vec<Position> {
+            fn to_positions(group: &Group) -> Vec<Position> {
                 group
-                    .borrow()
                     .cells()
                     .iter()
-                    .map(|c| c.borrow().pos())
+                    .map(|c| Position::from_index(*c, 4))
                     .collect()
             }
             assert_eq!(groups.len(), 3);
@@ -112,18 +101,36 @@ mod tests {
             );
         }
     }
+    mod find_groups_in_windows {
+        use super::*;
+        #[test]
+        fn it_finds_four_groups_of_the_cell_in_a_window() {
+            let game = NormalGame::new(
+                GameSetting::new(BlockSize {
+                    height: 3,
+                    width: 3,
+                })
+                .with_windows(),
+            );
+            let groups = game.find_groups(pos(3, 3));
+            assert_eq!(groups.len(), 4);
+            assert_eq!(groups[3].kind(), GroupKind::Window);
+            assert_eq!(game.find_groups(pos(4, 4)).len(), 3);
+        }
+    }
     mod find_answer_candidate_that_all_groups_hold {
         use super::*;
         #[test]
         fn it_find_answer_candidate_that_all_groups_hold() {
-            let game = NormalGame::new(setting());
+            let mut game = NormalGame::new(setting());
+            let group_indexes = game.groups_of(0).to_vec();
+            game.groups[group_indexes[0]].remove_answer_candidate(1);
+            game.groups[group_indexes[1]].remove_answer_candidate(1);
+            game.groups[group_indexes[2]].remove_answer_candidate(1);
+            game.groups[group_indexes[2]].remove_answer_candidate(3);
             let groups = game.find_groups(pos(0, 0));
-            groups[0].borrow_mut().remove_answer_candidate(1);
-            groups[1].borrow_mut().remove_answer_candidate(1);
-            groups[2].borrow_mut().remove_answer_candidate(1);
-            groups[2].borrow_mut().remove_answer_candidate(3);
             let answer_candidate = game.find_answer_candidate_that_all_groups_hold(groups);
-            assert_eq!(answer_candidate, [2, 4]);
+            assert_eq!(answer_candidate.iter().collect::<Vec<u8>>(), [2, 4]);
         }
     }
     mod remove_answer {
@@ -137,9 +144,9 @@ mod tests {
         fn to_answer_candidate_vec(game: &NormalGame) -> Vec<Vec<u8>> {
             let groups = game.find_groups(pos(1, 0));
             vec![
-                groups[0].borrow().answer_candidate(),
-                groups[1].borrow().answer_candidate(),
-                groups[2].borrow().answer_candidate(),
+                groups[0].answer_candidate(),
+                groups[1].answer_candidate(),
+                groups[2].answer_candidate(),
             ]
         }
         #[test]
@@ -171,15 +178,14 @@ mod tests {
                 to_answer_candidate_vec(&game),
                 [
                     vec![3, 4, 6, 7, 8],
-                    vec![1, 2, 3, 5, 7, 9, 8],
-                    vec![1, 4, 6, 7, 9, 8]
+                    vec![1, 2, 3, 5, 7, 8, 9],
+                    vec![1, 4, 6, 7, 8, 9]
                 ]
             );
             assert_eq!(removed_answer, Some(8));
             assert!(game
                 .find_cell(pos(1, 0))
                 .unwrap()
-                .borrow()
                 .answer_candidate()
                 .any(|c| *c == 8));
         }
@@ -220,12 +226,43 @@ mod tests {
             assert_eq!(
                 game.find_cell(pos(1, 0))
                     .unwrap()
-                    .borrow()
                     .answer_candidate()
-                    .map(|a| *a)
+                    .copied()
                     .collect::<Vec<u8>>(),
                 vec![7, 8]
             );
         }
+        #[test]
+        fn it_restores_the_answer_candidate_of_the_diagonals() {
+            let mut game = NormalGame::new(setting().with_diagonals());
+            assert_eq!(game.find_groups(pos(4, 4)).len(), 5);
+            game.set_answer(pos(4, 4), 5);
+            assert!(!game.find_cell(pos(0, 0)).unwrap().has_answer_candidate(5));
+            assert!(!game.find_cell(pos(8, 0)).unwrap().has_answer_candidate(5));
+            game.set_answer(pos(8, 8), 1);
+            assert_eq!(game.remove_answer(pos(4, 4)), Some(5));
+            assert!(game.find_cell(pos(0, 0)).unwrap().has_answer_candidate(5));
+            assert!(game.find_cell(pos(8, 0)).unwrap().has_answer_candidate(5));
+            // 1 is still removed by the answer on the main diagonal.
+            assert!(!game.find_cell(pos(0, 0)).unwrap().has_answer_candidate(1));
+            assert!(game.find_cell(pos(4, 4)).unwrap().has_answer_candidate(5));
+            assert!(!game.find_cell(pos(4, 4)).unwrap().has_answer_candidate(1));
+        }
+        #[test]
+        fn it_restores_the_answer_candidate_of_the_windows() {
+            let mut game = NormalGame::new(setting().with_windows());
+            // (3, 3) is in the top left window, which overlaps the blocks of (1, 1) and (5, 1).
+            game.set_answer(pos(3, 3), 5);
+            assert!(!game.find_cell(pos(1, 1)).unwrap().has_answer_candidate(5));
+            assert!(!game.find_cell(pos(3, 1)).unwrap().has_answer_candidate(5));
+            assert!(game.find_cell(pos(6, 6)).unwrap().has_answer_candidate(5));
+            game.set_answer(pos(1, 2), 7);
+            assert_eq!(game.remove_answer(pos(3, 3)), Some(5));
+            assert!(game.find_cell(pos(1, 1)).unwrap().has_answer_candidate(5));
+            assert!(game.find_cell(pos(3, 3)).unwrap().has_answer_candidate(5));
+            // 7 is still removed by the answer in the same window and block.
+            assert!(!game.find_cell(pos(3, 3)).unwrap().has_answer_candidate(7));
+            assert!(!game.find_cell(pos(2, 1)).unwrap().has_answer_candidate(7));
+        }
     }
 }
@@ -1,7 +1,21 @@
+use crate::normal_game::cage;
+use crate::normal_game::cage::Cage;
+use crate::normal_game::cage::CageError;
+use crate::normal_game::candidates::Candidates;
+use crate::normal_game::cell::Position;
+use crate::normal_game::relation::PeerRelation;
+use std::fmt;
+use std::sync::Arc;
+
 #[derive(Debug, Clone)]
 pub struct GameSetting {
     block_size: BlockSize,
     answer_candidate: Vec<u8>,
+    diagonals: bool,
+    windows: bool,
+    regions: Option<Arc<[usize]>>,
+    cages: Arc<[Cage]>,
+    peer_relations: Arc<[PeerRelation]>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -15,6 +29,11 @@ impl GameSetting {
         GameSetting {
             block_size,
             answer_candidate: (1..=(block_size.height * block_size.width)).collect(),
+            diagonals: false,
+            windows: false,
+            regions: None,
+            cages: Arc::new([]),
+            peer_relations: Arc::new([]),
         }
     }
     pub fn new_with_answer_candidate(
@@ -24,7 +43,117 @@ impl GameSetting {
         GameSetting {
             block_size,
             answer_candidate,
+            diagonals: false,
+            windows: false,
+            regions: None,
+            cages: Arc::new([]),
+            peer_relations: Arc::new([]),
+        }
+    }
+    /// Returns the setting with the answer_candidate replaced, keeping the variant constraints.
+    ///
+    /// バリアントの制約を維持したまま、answer_candidate を置き換えた設定を返却する。
+    pub fn with_answer_candidate(&self, answer_candidate: Vec<u8>) -> GameSetting {
+        GameSetting {
+            answer_candidate,
+            ..self.clone()
+        }
+    }
+    /// Returns the setting with the two main diagonals added as groups (Sudoku X).
+    ///
+    /// 2 本の対角線をグループとして追加した設定 (対角線ナンプレ) を返却する。
+    pub fn with_diagonals(mut self) -> GameSetting {
+        self.diagonals = true;
+        self
+    }
+    pub fn has_diagonals(&self) -> bool {
+        self.diagonals
+    }
+    /// Returns the setting with the windows of Hyper Sudoku (Windoku) added as groups.
+    /// The windows are the size of a block and are placed one cell apart from the edges and from each other,
+    /// so a 9x9 board has four 3x3 windows. A block size that has no room for a window gets no windows.
+    ///
+    /// ハイパーナンプレ (窓ナンプレ) の窓をグループとして追加した設定を返却する。
+    /// 窓はブロックと同じ大きさで、盤面の端および互いから 1 セルずつ離して配置するため、9x9 の盤面には 3x3 の窓が 4 つある。
+    /// 窓を配置する余地がないブロックの大きさでは窓は作られない。
+    pub fn with_windows(mut self) -> GameSetting {
+        self.windows = true;
+        self
+    }
+    pub fn has_windows(&self) -> bool {
+        self.windows
+    }
+    /// Returns the setting whose blocks are replaced by the irregular regions (Jigsaw).
+    /// `regions` has the region ID of each cell, indexed by `y * side_size + x`.
+    /// Each region must have exactly `side_size` cells and be connected vertically or horizontally.
+    ///
+    /// ブロックを不規則な領域に置き換えた設定 (ジグソーナンプレ) を返却する。
+    /// `regions` は `y * side_size + x` をインデックスとする各セルの領域の ID である。
+    /// 各領域はちょうど `side_size` 個のセルを持ち、上下左右に連結していなければならない。
+    pub fn with_regions(mut self, regions: Vec<usize>) -> Result<GameSetting, RegionError> {
+        validate_regions(&regions, self.side_size())?;
+        self.regions = Some(regions.into());
+        Ok(self)
+    }
+    /// Returns the region ID of each cell, if the blocks are replaced by the irregular regions.
+    ///
+    /// ブロックが不規則な領域に置き換えられている場合、各セルの領域の ID を返却する。
+    pub fn regions(&self) -> Option<&[usize]> {
+        self.regions.as_deref()
+    }
+    /// Returns the setting with the cages of Killer Sudoku.
+    /// The cages must be inside the board, must not overlap, and must have a sum that different answer_candidate can add up to.
+    ///
+    /// キラーナンプレのケージを持つ設定を返却する。
+    /// ケージは盤面の内側にあり、互いに重ならず、異なる answer_candidate の合計で表せる合計を持たなければならない。
+    pub fn with_cages(mut self, cages: Vec<Cage>) -> Result<GameSetting, CageError> {
+        cage::validate_cages(&cages, self.side_size(), self.candidates())?;
+        self.cages = cages.into();
+        Ok(self)
+    }
+    /// Returns the setting with the cages loaded from the text in the format of `cages_to_string`.
+    ///
+    /// `cages_to_string` の形式のテキストから読み込んだケージを持つ設定を返却する。
+    pub fn load_cages(self, text: &str) -> Result<GameSetting, CageError> {
+        let cages = cage::parse_cages(text)?;
+        self.with_cages(cages)
+    }
+    pub fn cages(&self) -> &[Cage] {
+        &self.cages
+    }
+    /// Returns the cages as text, such as `3:0,0 1,0|15:2,0 2,1 3,1`.
+    /// The cages are separated by `|`, and each cage is the sum followed by the positions `x,y` of the cells.
+    ///
+    /// ケージをテキストで返却する。例えば `3:0,0 1,0|15:2,0 2,1 3,1` のようになる。
+    /// ケージは `|` で区切り、各ケージは合計とそれに続くセルの位置 `x,y` で表す。
+    pub fn cages_to_string(&self) -> String {
+        cage::cages_to_string(&self.cages)
+    }
+    /// Returns the setting with the relation added, such as no equal answers a knight's move apart (Anti-Knight).
+    ///
+    /// 関係を追加した設定を返却する。例えば、ナイトの動きで移動できるセルに同じ解答を置かない (アンチナイト) などである。
+    pub fn with_peer_relation(mut self, relation: PeerRelation) -> GameSetting {
+        if !self.peer_relations.contains(&relation) {
+            let mut relations = self.peer_relations.to_vec();
+            relations.push(relation);
+            self.peer_relations = relations.into();
         }
+        self
+    }
+    pub fn peer_relations(&self) -> &[PeerRelation] {
+        &self.peer_relations
+    }
+    /// Returns true if the setting has constraints other than the rows, columns and rectangular blocks.
+    /// The games of such a setting cannot be shuffled, and the techniques that assume the standard rules are not applied.
+    ///
+    /// 行、列、長方形のブロック以外の制約を持つ設定である場合に true を返す。
+    /// このような設定のゲームはシャッフルできず、標準のルールを前提とするテクニックは適用されない。
+    pub fn is_variant(&self) -> bool {
+        self.diagonals
+            || self.windows
+            || self.regions.is_some()
+            || !self.cages.is_empty()
+            || !self.peer_relations.is_empty()
     }
     pub fn side_size(&self) -> u8 {
         self.block_size.height * self.block_size.width
@@ -32,6 +161,10 @@ impl GameSetting {
     pub fn answer_candidate(&self) -> Vec<u8> {
         self.answer_candidate.clone()
     }
+    /// Returns the answer_candidate as a set.
+    pub fn candidates(&self) -> Candidates {
+        self.answer_candidate.iter().collect()
+    }
     pub fn block_height(&self) -> u8 {
         self.block_size.height
     }
@@ -42,3 +175,182 @@ impl GameSetting {
         self.block_size
     }
 }
+
+/// The reason why a region map is invalid.
+///
+/// 領域の割り当てが不正である理由。
+#[derive(Debug, PartialEq, Clone)]
+pub enum RegionError {
+    /// The number of the region IDs does not match the number of the cells.
+    WrongCellCount { expected: usize, actual: usize },
+    /// The region does not have `side_size` cells.
+    WrongRegionSize {
+        region: usize,
+        expected: u8,
+        actual: usize,
+    },
+    /// The cells of the region are not connected.
+    Disconnected { region: usize },
+}
+
+impl fmt::Display for RegionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegionError::WrongCellCount { expected, actual } => {
+                write!(f, "expected {} region IDs, but found {}", expected, actual)
+            }
+            RegionError::WrongRegionSize {
+                region,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "expected {} cells in region {}, but found {}",
+                expected, region, actual
+            ),
+            RegionError::Disconnected { region } => {
+                write!(f, "the cells of region {} are not connected", region)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RegionError {}
+
+fn validate_regions(regions: &[usize], side_size: u8) -> Result<(), RegionError> {
+    let cell_count = side_size as usize * side_size as usize;
+    if regions.len() != cell_count {
+        return Err(RegionError::WrongCellCount {
+            expected: cell_count,
+            actual: regions.len(),
+        });
+    }
+    let mut ids: Vec<usize> = regions.to_vec();
+    ids.sort_unstable();
+    ids.dedup();
+    for region in ids {
+        let cells: Vec<usize> = (0..cell_count).filter(|i| regions[*i] == region).collect();
+        if cells.len() != side_size as usize {
+            return Err(RegionError::WrongRegionSize {
+                region,
+                expected: side_size,
+                actual: cells.len(),
+            });
+        }
+        // Visit the cells reachable from the first cell through the neighbours in the same region.
+        let mut visited = vec![cells[0]];
+        let mut stack = vec![cells[0]];
+        while let Some(index) = stack.pop() {
+            let pos = Position::from_index(index, side_size);
+            let neighbours = [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                .iter()
+                .map(|(dx, dy)| (pos.x() as i16 + dx, pos.y() as i16 + dy))
+                .filter(|(x, y)| {
+                    (0..side_size as i16).contains(x) && (0..side_size as i16).contains(y)
+                })
+                .map(|(x, y)| Position::new(x as u8, y as u8).index(side_size));
+            for neighbour in neighbours {
+                if regions[neighbour] == region && !visited.contains(&neighbour) {
+                    visited.push(neighbour);
+                    stack.push(neighbour);
+                }
+            }
+        }
+        if visited.len() != cells.len() {
+            return Err(RegionError::Disconnected { region });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn setting() -> GameSetting {
+        GameSetting::new(BlockSize {
+            height: 2,
+            width: 2,
+        })
+    }
+    mod with_regions {
+        use super::*;
+        #[test]
+        fn it_accepts_connected_regions() {
+            let regions = vec![0, 0, 0, 1, 2, 0, 1, 1, 2, 2, 3, 1, 2, 3, 3, 3];
+            let setting = setting().with_regions(regions.clone()).unwrap();
+            assert_eq!(setting.regions(), Some(regions.as_slice()));
+            assert!(setting.is_variant());
+        }
+        #[test]
+        fn it_rejects_wrong_cell_count() {
+            assert_eq!(
+                setting().with_regions(vec![0; 9]).unwrap_err(),
+                RegionError::WrongCellCount {
+                    expected: 16,
+                    actual: 9
+                }
+            );
+        }
+        #[test]
+        fn it_rejects_wrong_region_size() {
+            let regions = vec![0, 0, 0, 0, 0, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3];
+            assert_eq!(
+                setting().with_regions(regions).unwrap_err(),
+                RegionError::WrongRegionSize {
+                    region: 0,
+                    expected: 4,
+                    actual: 5
+                }
+            );
+        }
+        #[test]
+        fn it_rejects_disconnected_region() {
+            let regions = vec![0, 0, 0, 1, 1, 1, 1, 0, 2, 2, 2, 2, 3, 3, 3, 3];
+            assert_eq!(
+                setting().with_regions(regions).unwrap_err(),
+                RegionError::Disconnected { region: 0 }
+            );
+        }
+    }
+    mod with_cages {
+        use super::*;
+        #[test]
+        fn it_loads_and_formats_the_cages() {
+            let text = "3:0,0 1,0|7:2,0 3,0 3,1";
+            let setting = setting().load_cages(text).unwrap();
+            assert_eq!(setting.cages().len(), 2);
+            assert_eq!(setting.cages()[1].sum(), 7);
+            assert_eq!(setting.cages_to_string(), text);
+            assert!(setting.is_variant());
+            assert!(!setting.with_cages(vec![]).unwrap().is_variant());
+        }
+        #[test]
+        fn it_rejects_invalid_cages() {
+            assert_eq!(
+                setting().load_cages("3:0,0 4,0").unwrap_err(),
+                CageError::OutOfRange {
+                    pos: Position::new(4, 0)
+                }
+            );
+            assert_eq!(
+                setting().load_cages("9:0,0 1,0").unwrap_err(),
+                CageError::ImpossibleSum { cage: 0, sum: 9 }
+            );
+        }
+    }
+    mod with_peer_relation {
+        use super::*;
+        #[test]
+        fn it_adds_the_relation_only_once() {
+            let setting = setting()
+                .with_peer_relation(PeerRelation::AntiKnight)
+                .with_peer_relation(PeerRelation::AntiKing)
+                .with_peer_relation(PeerRelation::AntiKnight);
+            assert_eq!(
+                setting.peer_relations(),
+                &[PeerRelation::AntiKnight, PeerRelation::AntiKing]
+            );
+            assert!(setting.is_variant());
+        }
+    }
+}
@@ -1,17 +1,21 @@
-use crate::normal_game::cell::Cell;
 use crate::normal_game::cell::Position;
 use crate::normal_game::NormalGame;
-use core::cell::RefCell;
 use rand::prelude::*;
-use std::rc::Rc;
+use std::cell::RefCell;
 
 impl NormalGame {
     pub fn shuffle(&self) -> NormalGame {
+        self.shuffle_with_rng(&mut thread_rng())
+    }
+    /// Shuffle the game with the specified random number generator.
+    /// The same generator state always yields the same result.
+    ///
+    /// 指定した乱数生成器でゲームをシャッフルする。
+    /// 乱数生成器の状態が同じであれば、常に同じ結果となる。
+    pub fn shuffle_with_rng<R: Rng + ?Sized>(&self, rng: &mut R) -> NormalGame {
+        let rng = RefCell::new(rng);
         let mut game = self.clone();
-        game.shuffle_manualy(|len: usize| -> usize {
-            let mut rng = thread_rng();
-            rng.gen_range(0..len)
-        });
+        game.shuffle_manualy(|len: usize| -> usize { rng.borrow_mut().gen_range(0..len) });
         game
     }
     pub fn reverse(&self) -> NormalGame {
@@ -45,134 +49,83 @@ impl NormalGame {
     where
         F: Fn(usize) -> usize,
     {
+        let mut row_map: Vec<u8> = (0..self.setting().side_size()).collect();
         for block_index in 0..self.setting().block_width() {
             let start_y = block_index * self.setting().block_height();
             let mut row_indexes: Vec<u8> =
                 (start_y..(self.setting().block_height() + start_y)).collect();
-            // Since it is not possible to iterate while changing the y-coordinate,
-            // list the cells with the specified y-coordinate in advance.
-            let mut cell_lines: Vec<Vec<&Rc<RefCell<Cell>>>> = Vec::new();
-            for row in row_indexes.clone() {
-                cell_lines.push(
-                    self.cells()
-                        .iter()
-                        .filter(|c| c.borrow().pos().y() == row)
-                        .collect(),
-                );
-            }
-            for cell_line in cell_lines {
+            for row in start_y..(self.setting().block_height() + start_y) {
                 let i = random_index(row_indexes.len());
-                let to = row_indexes.remove(i);
-                cell_line.iter().for_each(|c| {
-                    let pos = c.borrow().pos();
-                    c.borrow_mut().move_to(Position::new(pos.x(), to));
-                });
+                row_map[row as usize] = row_indexes.remove(i);
             }
         }
+        self.move_answers(|pos| Position::new(pos.x(), row_map[pos.y() as usize]));
     }
     fn shuffle_cols<F>(&mut self, random_index: F)
     where
         F: Fn(usize) -> usize,
     {
+        let mut col_map: Vec<u8> = (0..self.setting().side_size()).collect();
         for block_index in 0..self.setting().block_height() {
             let start_x = block_index * self.setting().block_width();
             let mut col_indexes: Vec<u8> =
                 (start_x..(self.setting().block_width() + start_x)).collect();
-            // Since it is not possible to iterate while changing the x-coordinate,
-            // list the cells with the specified x-coordinate in advance.
-            let mut cell_lines: Vec<Vec<&Rc<RefCell<Cell>>>> = Vec::new();
-            for col in col_indexes.clone() {
-                cell_lines.push(
-                    self.cells()
-                        .iter()
-                        .filter(|c| c.borrow().pos().x() == col)
-                        .collect(),
-                );
-            }
-            for cell_line in cell_lines {
+            for col in start_x..(self.setting().block_width() + start_x) {
                 let i = random_index(col_indexes.len());
-                let to = col_indexes.remove(i);
-                cell_line.iter().for_each(|c| {
-                    let pos = c.borrow().pos();
-                    c.borrow_mut().move_to(Position::new(to, pos.y()));
-                });
+                col_map[col as usize] = col_indexes.remove(i);
             }
         }
+        self.move_answers(|pos| Position::new(col_map[pos.x() as usize], pos.y()));
     }
     fn shuffle_row_blocks<F>(&mut self, random_index: F)
     where
         F: Fn(usize) -> usize,
     {
+        let block_height = self.setting().block_height();
         let mut block_indexes: Vec<u8> = (0..self.setting().block_width()).collect();
-
-        let mut block_lines: Vec<Vec<&Rc<RefCell<Cell>>>> = Vec::new();
-
-        // ブロック行の単位でセルの配列を収集する
-        for block_row_index in block_indexes.clone() {
-            let mut inner_lines: Vec<Vec<&Rc<RefCell<Cell>>>> = Vec::new();
-            for inner_row_no in 0..self.setting().block_height() {
-                inner_lines.push(
-                    self.cells()
-                        .iter()
-                        .filter(|c| {
-                            c.borrow().pos().y()
-                                == block_row_index * self.setting().block_height() + inner_row_no
-                        })
-                        .collect(),
-                );
-            }
-            block_lines.push(inner_lines.concat());
-        }
-
-        // セルの移動を行う
-        for (block_row, block_line) in block_lines.iter().enumerate() {
-            let i = random_index(block_indexes.len());
-            let to = block_indexes.remove(i);
-            // block_line 内のセルを移動する。現在の block row と to の値の差分に self.setting().block_height() の値を掛け算し、セルの y 座標をムーブするのが良い気がする。
-            let move_count =
-                ((to as i16) - (block_row as i16)) * (self.setting().block_height() as i16);
-            block_line.iter().for_each(|c| {
-                let pos = c.borrow().pos();
-                c.borrow_mut().move_to(pos.move_y(move_count))
-            });
-        }
+        // ブロック行ごとに移動先のブロック行を決める
+        let block_map: Vec<u8> = (0..self.setting().block_width())
+            .map(|_| {
+                let i = random_index(block_indexes.len());
+                block_indexes.remove(i)
+            })
+            .collect();
+        self.move_answers(|pos| {
+            let to = block_map[(pos.y() / block_height) as usize];
+            Position::new(pos.x(), to * block_height + pos.y() % block_height)
+        });
     }
     fn shuffle_col_blocks<F>(&mut self, random_index: F)
     where
         F: Fn(usize) -> usize,
     {
+        let block_width = self.setting().block_width();
         let mut block_indexes: Vec<u8> = (0..self.setting().block_height()).collect();
+        // ブロック列ごとに移動先のブロック列を決める
+        let block_map: Vec<u8> = (0..self.setting().block_height())
+            .map(|_| {
+                let i = random_index(block_indexes.len());
+                block_indexes.remove(i)
+            })
+            .collect();
+        self.move_answers(|pos| {
+            let to = block_map[(pos.x() / block_width) as usize];
+            Position::new(to * block_width + pos.x() % block_width, pos.y())
+        });
+    }
 
-        let mut block_lines: Vec<Vec<&Rc<RefCell<Cell>>>> = Vec::new();
-
-        // ブロック行の単位でセルの配列を収集する
-        for block_col_index in block_indexes.clone() {
-            let mut inner_lines: Vec<Vec<&Rc<RefCell<Cell>>>> = Vec::new();
-            for inner_col_no in 0..self.setting().block_width() {
-                inner_lines.push(
-                    self.cells()
-                        .iter()
-                        .filter(|c| {
-                            c.borrow().pos().x()
-                                == block_col_index * self.setting().block_width() + inner_col_no
-                        })
-                        .collect(),
-                );
+    /// Move each answer to the position returned by `to`, and rebuild the game.
+    fn move_answers<F>(&mut self, to: F)
+    where
+        F: Fn(Position) -> Position,
+    {
+        let mut game = NormalGame::new(self.setting().clone());
+        for cell in self.cells().iter() {
+            if let Some(answer) = cell.answer() {
+                game.set_answer(to(cell.pos()), answer);
             }
-            block_lines.push(inner_lines.concat());
-        }
-
-        // セルの移動を行う
-        for (block_col, block_line) in block_lines.iter().enumerate() {
-            let i = random_index(block_indexes.len());
-            let to = block_indexes.remove(i);
-            let move_count =
-                ((to as i16) - (block_col as i16)) * (self.setting().block_width() as i16);
-            block_line.iter().for_each(|c| {
-                let pos = c.borrow().pos();
-                c.borrow_mut().move_to(pos.move_x(move_count))
-            });
         }
+        *self = game;
     }
 }
 
@@ -253,4 +206,15 @@ mod tests {
             "582397146|496152387|371486952|148639725|629875431|735241698|953768214|264513879|817924563"
         );
     }
+    #[test]
+    fn test_shuffle_with_rng() {
+        let mut game = NormalGame::new(GameSetting::new(BlockSize {
+            height: 3,
+            width: 3,
+        }));
+        game.load("582397146|496152387|371486952|148639725|629875431|735241698|953768214|264513879|817924563");
+        let shuffled1 = game.shuffle_with_rng(&mut StdRng::seed_from_u64(1));
+        let shuffled2 = game.shuffle_with_rng(&mut StdRng::seed_from_u64(1));
+        assert_eq!(shuffled1.to_string(), shuffled2.to_string());
+    }
 }
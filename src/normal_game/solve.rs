@@ -1,47 +1,87 @@
+use crate::normal_game::candidates::Candidates;
 use crate::normal_game::cell::Cell;
 use crate::normal_game::cell::Position;
+use crate::normal_game::technique::Technique;
 use crate::normal_game::GameState;
 use crate::normal_game::NormalGame;
 
+/// The techniques that remove answer_candidate, applied by `solve` and `simple_solve` when the singles run out,
+/// before guessing or giving up. They are in ascending order of score.
+///
+/// answer_candidate を除去するテクニック。`solve` と `simple_solve` において、唯一の解答がなくなった際に、
+/// 仮定を行う前、または諦める前に適用する。スコアの昇順に並んでいる。
+const ELIMINATION_TECHNIQUES: [Technique; 8] = [
+    Technique::Pointing,
+    Technique::Claiming,
+    Technique::NakedPair,
+    Technique::HiddenPair,
+    Technique::NakedTriple,
+    Technique::HiddenTriple,
+    Technique::NakedQuad,
+    Technique::HiddenQuad,
+];
+
 impl NormalGame {
     /// Solve the game.
+    /// When the singles run out, the answer_candidate are removed with Pointing, Claiming and the subsets before guessing.
     /// If the problem is solved, it returns a NormalGame instance with the solution filled in.
     /// If the problem is inconsistent, it returns None.
     /// Does not consider the case where there are multiple solutions.
     ///
     /// ゲームを解く。
+    /// 唯一の解答がなくなった場合は、仮定を行う前に Pointing、Claiming と Subset で answer_candidate を除去する。
     /// 問題を解けた場合は、解答を記入済みの NormalGame インスタンスを返す。
     /// 問題に矛盾がある場合は None を返す。
     /// 複数の解答が存在する場合は考慮していない。
     pub fn solve(&self) -> Option<NormalGame> {
         let mut game = self.clone();
-        loop {
-            let before_count = game.answered_count();
-            Self::fill_lonely_in_cell(&mut game);
-            Self::fill_lonely_in_group(&mut game);
-            match game.check_status() {
-                GameState::Complete => return Some(game),
-                GameState::Failure => return None,
-                _ => {}
-            }
-            if before_count == game.answered_count() {
-                return self.assume_and_solve(&game);
-            }
+        match game.fill_lonely_and_eliminate() {
+            GameState::Complete => Some(game),
+            GameState::Failure => None,
+            GameState::Solving => self.assume_and_solve(&game),
         }
     }
+    /// Returns an iterator that yields every solution of the game one at a time.
+    /// Solutions are searched lazily with the same backtracking as `solve`, so the first one is the same as the result of `solve`.
+    /// The order is deterministic and follows the order of the answer_candidate.
+    ///
+    /// ゲームの全ての解答を 1 つずつ返すイテレータを返却する。
+    /// 解答は `solve` と同じバックトラックで必要な分だけ探索されるため、最初の解答は `solve` の結果と同じである。
+    /// 順序は決定的であり、answer_candidate の順序に従う。
+    pub fn solutions(&self) -> Solutions {
+        Solutions {
+            stack: vec![self.clone()],
+        }
+    }
+
+    /// Count the solutions of the game, up to `limit`.
+    /// It stops searching as soon as `limit` solutions are found.
+    ///
+    /// ゲームの解答の数を `limit` を上限として数える。
+    /// `limit` 個の解答が見つかった時点で探索を打ち切る。
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        self.solutions().take(limit).count()
+    }
+
+    /// Returns true if the game has exactly one solution.
+    ///
+    /// ゲームの解答がただ 1 つである場合に true を返す。
+    pub fn has_unique_solution(&self) -> bool {
+        self.count_solutions(2) == 1
+    }
+
+    /// Solve the game like `solve`, but without guessing.
+    /// It returns None if the singles, the intersections and the subsets are not enough.
+    /// The generator removes the answers as long as the game can be solved by this.
+    ///
+    /// `solve` と同様にゲームを解くが、仮定は行わない。
+    /// 唯一の解答、Pointing、Claiming と Subset で解けない場合は None を返す。
+    /// 生成器はこれで解ける限り解答を取り除く。
     pub fn simple_solve(&self) -> Option<NormalGame> {
         let mut game = self.clone();
-        loop {
-            let before_count = game.answered_count();
-            Self::fill_lonely_in_cell(&mut game);
-            Self::fill_lonely_in_group(&mut game);
-            match game.check_status() {
-                GameState::Complete => return Some(game),
-                _ => {}
-            }
-            if before_count == game.answered_count() {
-                return None;
-            }
+        match game.fill_lonely_and_eliminate() {
+            GameState::Complete => Some(game),
+            _ => None,
         }
     }
 
@@ -55,23 +95,22 @@ impl NormalGame {
         // Clone to avoid the effects of sorting.
         let mut cells = game.cells().clone();
         cells.sort_by(|a, b| {
-            a.borrow()
-                .answer_candidate_count()
-                .partial_cmp(&b.borrow().answer_candidate_count())
+            a.answer_candidate_count()
+                .partial_cmp(&b.answer_candidate_count())
                 .unwrap()
         });
-        let cells: Vec<&std::rc::Rc<std::cell::RefCell<Cell>>> = cells
+        let cells: Vec<&Cell> = cells
             .iter()
-            .filter(|c| c.borrow().answer_candidate_count() != 0)
+            .filter(|c| c.answer_candidate_count() != 0)
             .collect();
         if cells.len() == 0 {
             return Some(game.clone());
         }
 
         // let mut solved_game: Option<NormalGame> = None;
-        for candidate in cells[0].borrow().answer_candidate() {
+        for candidate in Self::ordered_answer_candidate(game, cells[0].candidates()) {
             let mut new_game = game.clone();
-            new_game.set_answer(cells[0].borrow().pos(), *candidate);
+            new_game.set_answer(cells[0].pos(), candidate);
             // let solver = Solver::new(&new_game);
             let new_game = new_game.solve();
             if new_game.is_some() {
@@ -83,15 +122,60 @@ impl NormalGame {
         None
     }
 
+    /// Sort the candidates in the order of the answer_candidate of the setting.
+    /// The order in which assumptions are made follows this order.
+    ///
+    /// 候補を設定の answer_candidate の順に並べる。仮定はこの順序で行う。
+    pub(crate) fn ordered_answer_candidate(game: &NormalGame, candidates: Candidates) -> Vec<u8> {
+        game.setting()
+            .answer_candidate()
+            .into_iter()
+            .filter(|a| candidates.contains(*a))
+            .collect()
+    }
+
+    /// Fill in lonely answers until nothing changes, and return the state of the game.
+    ///
+    /// 変化がなくなるまで唯一の解答を記入し、ゲームの状態を返却する。
+    pub(crate) fn fill_lonely(&mut self) -> GameState {
+        loop {
+            let before_count = self.answered_count();
+            Self::fill_lonely_in_cell(self);
+            Self::fill_lonely_in_group(self);
+            let state = self.check_status();
+            if state != GameState::Solving || before_count == self.answered_count() {
+                return state;
+            }
+        }
+    }
+
+    /// Fill in lonely answers like `fill_lonely`, and when they run out,
+    /// remove the answer_candidate with the elimination techniques and continue.
+    ///
+    /// `fill_lonely` と同様に唯一の解答を記入し、それがなくなった場合は
+    /// 除去のテクニックで answer_candidate を除去して続行する。
+    pub(crate) fn fill_lonely_and_eliminate(&mut self) -> GameState {
+        loop {
+            let state = self.fill_lonely();
+            if state != GameState::Solving {
+                return state;
+            }
+            match self.find_step(&ELIMINATION_TECHNIQUES) {
+                Some(step) => self.apply_step(&step),
+                None => return state,
+            }
+        }
+    }
+
     /// If there is only one possible answer in each cell, confirm it.
     fn fill_lonely_in_cell(game: &mut NormalGame) {
         let pos_and_answers: Vec<(Position, u8)> = game
             .cells()
             .iter()
             .map(|c| {
-                let answer = c.borrow().get_lonely();
+                let answer = c.get_lonely();
                 if let Some(answer) = answer {
-                    Some((c.borrow().pos(), answer))
+                    Some((c.pos(), answer))
                 } else {
                     None
                 }
@@ -108,7 +192,7 @@ impl NormalGame {
     fn fill_lonely_in_group(game: &mut NormalGame) {
         let mut fillable_pos_answer: Vec<(Position, u8)> = vec![];
         for group in game.groups().iter() {
-            for (pos, answer) in group.borrow().get_lonely().iter() {
+            for (pos, answer) in group.get_lonely(game.cells()).iter() {
                 fillable_pos_answer.push((*pos, *answer))
             }
         }
@@ -119,6 +203,45 @@ impl NormalGame {
     }
 }
 
+/// An iterator over the solutions of a NormalGame, created by `NormalGame::solutions`.
+///
+/// NormalGame の解答を列挙するイテレータ。`NormalGame::solutions` で生成する。
+pub struct Solutions {
+    stack: Vec<NormalGame>,
+}
+
+impl Iterator for Solutions {
+    type Item = NormalGame;
+
+    fn next(&mut self) -> Option<NormalGame> {
+        while let Some(mut game) = self.stack.pop() {
+            match game.fill_lonely_and_eliminate() {
+                GameState::Complete => return Some(game),
+                GameState::Failure => continue,
+                GameState::Solving => {}
+            }
+            let cell = match game
+                .cells()
+                .iter()
+                .filter(|c| c.answer_candidate_count() != 0)
+                .min_by_key(|c| c.answer_candidate_count())
+            {
+                Some(cell) => cell,
+                None => continue,
+            };
+            let pos = cell.pos();
+            let candidates = NormalGame::ordered_answer_candidate(&game, cell.candidates());
+            // Push in reverse order so that the first answer_candidate is searched first.
+            for candidate in candidates.into_iter().rev() {
+                let mut new_game = game.clone();
+                new_game.set_answer(pos, candidate);
+                self.stack.push(new_game);
+            }
+        }
+        None
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -149,10 +272,7 @@ mod test {
             NormalGame::fill_lonely_in_group(&mut game);
 
             fn get_answer(game: &NormalGame, x: u8, y: u8) -> Option<u8> {
-                game.find_cell(Position::new(x, y))
-                    .unwrap()
-                    .borrow()
-                    .answer()
+                game.find_cell(Position::new(x, y)).unwrap().answer()
             }
 
             assert_eq!(get_answer(&game, 0, 0), Some(1));
@@ -283,6 +403,143 @@ mod test {
             }
         }
     }
+    mod simple_solve {
+        use super::*;
+        #[test]
+        fn it_solves_the_issue_that_needs_subsets() {
+            let mut game = NormalGame::new(GameSetting::new(BlockSize {
+                height: 3,
+                width: 3,
+            }));
+            game.load(" 1 8   3 |6  7    4|  5 9    | 8   7 4 |  4 5  6 |5       7|  1  45  |23  8    |   9  3 2");
+            let mut solved_game = game.simple_solve().unwrap();
+            assert_eq!(solved_game.to_string(), game.solve().unwrap().to_string());
+            assert_eq!(solved_game.check_status(), GameState::Complete);
+        }
+        #[test]
+        fn it_solves_the_issue_that_needs_pointing() {
+            let mut game = NormalGame::new(GameSetting::new(BlockSize {
+                height: 3,
+                width: 3,
+            }));
+            game.load("  2 43   |6     19 |4  6    2| 7   9   |154      |   5   73|    28 1 |  5 6   9|  8   34 ");
+            let mut solved_game = game.simple_solve().unwrap();
+            assert_eq!(solved_game.to_string(), game.solve().unwrap().to_string());
+            assert_eq!(solved_game.check_status(), GameState::Complete);
+        }
+        #[test]
+        fn it_gives_up_without_guessing() {
+            let game = NormalGame::new(GameSetting::new(BlockSize {
+                height: 3,
+                width: 3,
+            }));
+            assert!(game.simple_solve().is_none());
+        }
+    }
+    mod count_solutions {
+        use super::*;
+        fn setting() -> GameSetting {
+            GameSetting::new(BlockSize {
+                height: 3,
+                width: 3,
+            })
+        }
+        #[test]
+        fn it_returns_1_for_unique_issue() {
+            let mut game = NormalGame::new(setting());
+            game.load(
+                "5 2 9 1|   1   8|3    6  2| 4    7|6       1|  5    9|9  7    4| 6   3|  7 2 5 3",
+            );
+            assert_eq!(game.count_solutions(10), 1);
+            assert!(game.has_unique_solution());
+        }
+        #[test]
+        fn it_counts_up_to_limit() {
+            let game = NormalGame::new(GameSetting::new(BlockSize {
+                height: 2,
+                width: 2,
+            }));
+            assert_eq!(game.count_solutions(5), 5);
+            assert!(!game.has_unique_solution());
+        }
+        #[test]
+        fn it_counts_all_solutions_of_empty_4x4() {
+            let game = NormalGame::new(GameSetting::new(BlockSize {
+                height: 2,
+                width: 2,
+            }));
+            assert_eq!(game.count_solutions(1000), 288);
+        }
+        #[test]
+        fn it_counts_multiple_solutions() {
+            let mut game = NormalGame::new(setting());
+            // The 5 in the top left is removed from a unique issue, and there are 4 solutions.
+            game.load(
+                "  2 9 1|   1   8|3    6  2| 4    7|6       1|  5    9|9  7    4| 6   3|  7 2 5 3",
+            );
+            assert_eq!(game.count_solutions(10), 4);
+            assert_eq!(game.count_solutions(2), 2);
+            assert!(!game.has_unique_solution());
+        }
+        #[test]
+        fn it_returns_0_for_inconsistent_issue() {
+            let mut game = NormalGame::new(setting());
+            game.load("55");
+            assert_eq!(game.count_solutions(10), 0);
+            assert!(!game.has_unique_solution());
+        }
+    }
+    mod solutions {
+        use super::*;
+        #[test]
+        fn it_yields_all_solutions_of_empty_4x4() {
+            let game = NormalGame::new(GameSetting::new(BlockSize {
+                height: 2,
+                width: 2,
+            }));
+            let solutions: Vec<String> = game.solutions().map(|g| g.to_string()).collect();
+            assert_eq!(solutions.len(), 288);
+            assert!(solutions.iter().all(|s| !s.contains(' ')));
+            for (i, solution) in solutions.iter().enumerate() {
+                assert!(!solutions[i + 1..].contains(solution));
+            }
+        }
+        #[test]
+        fn first_solution_is_same_as_solve() {
+            let mut game = NormalGame::new(GameSetting::new(BlockSize {
+                height: 2,
+                width: 3,
+            }));
+            game.load("1");
+            assert_eq!(
+                game.solutions().next().unwrap().to_string(),
+                game.solve().unwrap().to_string()
+            );
+        }
+        #[test]
+        fn order_is_deterministic() {
+            let mut game = NormalGame::new(GameSetting::new(BlockSize {
+                height: 3,
+                width: 3,
+            }));
+            game.load(
+                "  2 9 1|   1   8|3    6  2| 4    7|6       1|  5    9|9  7    4| 6   3|  7 2 5 3",
+            );
+            let first: Vec<String> = game.solutions().map(|g| g.to_string()).collect();
+            let second: Vec<String> = game.solutions().map(|g| g.to_string()).collect();
+            assert_eq!(first.len(), 4);
+            assert_eq!(first, second);
+        }
+        #[test]
+        fn it_yields_nothing_for_inconsistent_issue() {
+            let mut game = NormalGame::new(GameSetting::new(BlockSize {
+                height: 3,
+                width: 3,
+            }));
+            game.load("55");
+            assert!(game.solutions().next().is_none());
+        }
+    }
     mod it_can_specify_arbitrary_answer_candidate {
         // It is possible to specify an arbitrary answer_candidate at the time of game generation.
         use super::*;
@@ -309,7 +566,7 @@ mod test {
                 vec![4, 3, 2, 1],
             ));
             let solved_game = game.solve();
-            assert_eq!(solved_game.unwrap().to_string(), "4321|2143|1432|3214")
+            assert_eq!(solved_game.unwrap().to_string(), "4321|2143|3412|1234")
         }
     }
 }
@@ -0,0 +1,112 @@
+use crate::normal_game::technique::Technique;
+use crate::normal_game::NormalGame;
+
+/// The strategy used to solve a game.
+///
+/// - Backtracking: Fill in the singles and guess when stuck. The same as `NormalGame::solve`.
+///   It is the recommended strategy for validation.
+/// - Logical: Apply the techniques as a human would and never guess.
+///   It gives up if the techniques are not enough, so it is suitable for grading.
+/// - DancingLinks: Solve the game as an exact cover problem. It is not faster than Backtracking,
+///   but it finds the same unique solution in a different way, so it is suitable for cross-checking.
+///
+/// ゲームを解く際の戦略。
+///
+/// - Backtracking: 唯一の解答を記入し、行き詰まった場合は仮定を行う。`NormalGame::solve` と同じである。
+///   検証にはこの戦略を推奨する。
+/// - Logical: 人間と同じようにテクニックを適用し、仮定は行わない。
+///   テクニックで解けない場合は諦めるため、難易度の判定に適している。
+/// - DancingLinks: ゲームを完全被覆問題として解く。Backtracking より高速ではないが、
+///   同じ唯一の解答を異なる方法で見つけるため、照合に適している。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SolveStrategy {
+    #[default]
+    Backtracking,
+    Logical,
+    DancingLinks,
+}
+
+impl SolveStrategy {
+    /// Returns the techniques used by the logical strategy, which are all the techniques except trial and error.
+    ///
+    /// Logical で使用するテクニックを返却する。試行錯誤以外の全てのテクニックである。
+    pub fn logical_techniques() -> Vec<Technique> {
+        Technique::ALL
+            .iter()
+            .filter(|t| **t != Technique::TrialAndError)
+            .copied()
+            .collect()
+    }
+}
+
+impl NormalGame {
+    /// Solve the game with the strategy.
+    /// If the problem is solved, it returns a NormalGame instance with the solution filled in.
+    /// If the problem is inconsistent, or the strategy is Logical and the techniques are not enough, it returns None.
+    ///
+    /// 指定した戦略でゲームを解く。
+    /// 問題を解けた場合は、解答を記入済みの NormalGame インスタンスを返す。
+    /// 問題に矛盾がある場合や、戦略が Logical でテクニックで解けない場合は None を返す。
+    pub fn solve_with(&self, strategy: SolveStrategy) -> Option<NormalGame> {
+        match strategy {
+            SolveStrategy::Backtracking => self.solve(),
+            SolveStrategy::Logical => {
+                let solution = self.solve_logically(&SolveStrategy::logical_techniques());
+                if solution.is_solved() {
+                    Some(solution.game)
+                } else {
+                    None
+                }
+            }
+            SolveStrategy::DancingLinks => self.solve_with_dlx(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normal_game::setting::BlockSize;
+    use crate::normal_game::setting::GameSetting;
+    fn game(issue: &str) -> NormalGame {
+        let mut game = NormalGame::new(GameSetting::new(BlockSize {
+            height: 3,
+            width: 3,
+        }));
+        game.load(issue);
+        game
+    }
+    const STRATEGIES: [SolveStrategy; 3] = [
+        SolveStrategy::Backtracking,
+        SolveStrategy::Logical,
+        SolveStrategy::DancingLinks,
+    ];
+    #[test]
+    fn all_strategies_return_the_same_solution() {
+        let game = game(" 7     6 |6   1   3|  54 87  |  8   4  | 1  3  5 |  9   1  |  35 12  |7   2   8| 5     9 ");
+        for strategy in STRATEGIES.iter() {
+            assert_eq!(
+                game.solve_with(*strategy).unwrap().to_string(),
+                "174392865|682715943|935468721|528176439|417839652|369254187|893541276|746923518|251687394"
+            );
+        }
+    }
+    #[test]
+    fn logical_strategy_refuses_to_guess() {
+        let game = game("4     8 5| 3       |   7     |  2     6 |    8 4  |    1    |   6 3 7 |5  2     |1 4      ");
+        assert!(game.solve_with(SolveStrategy::Logical).is_none());
+        assert!(game.solve_with(SolveStrategy::Backtracking).is_some());
+        assert!(game.solve_with(SolveStrategy::DancingLinks).is_some());
+    }
+    #[test]
+    fn all_strategies_return_none_for_inconsistent_game() {
+        let game = game("11");
+        for strategy in STRATEGIES.iter() {
+            assert!(game.solve_with(*strategy).is_none());
+        }
+    }
+    #[test]
+    fn default_strategy_is_backtracking() {
+        assert_eq!(SolveStrategy::default(), SolveStrategy::Backtracking);
+    }
+}
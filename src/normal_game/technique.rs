@@ -0,0 +1,487 @@
+use crate::normal_game::cell::Position;
+use crate::normal_game::difficulty::Difficulty;
+use crate::normal_game::GameState;
+use crate::normal_game::NormalGame;
+
+mod chain;
+mod fish;
+mod intersection;
+pub mod link;
+mod single;
+mod subset;
+mod trial;
+mod uniqueness;
+
+/// Human techniques to solve the game, in ascending order of score.
+///
+/// ゲームを解くための人間向けのテクニック。スコアの昇順に並んでいる。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Technique {
+    /// The answer_candidate is held by only one cell in a group.
+    HiddenSingle,
+    /// The cell has only one answer_candidate.
+    NakedSingle,
+    /// An answer_candidate in a block is held only by the cells in one row or column.
+    Pointing,
+    /// An answer_candidate in a row or column is held only by the cells in one block.
+    Claiming,
+    /// Two cells in a group hold only the same two answer_candidate.
+    NakedPair,
+    /// An answer_candidate in two rows (columns) is held only by the cells in the same two columns (rows).
+    XWing,
+    /// Two answer_candidate are held only by the same two cells in a group.
+    HiddenPair,
+    /// An X-Wing with extra cells, called fins.
+    FinnedXWing,
+    /// Three cells in a group hold only the same three answer_candidate.
+    NakedTriple,
+    /// A finned X-Wing that is incomplete without the fins.
+    SashimiXWing,
+    /// An answer_candidate in three rows (columns) is held only by the cells in the same three columns (rows).
+    Swordfish,
+    /// Three answer_candidate are held only by the same three cells in a group.
+    HiddenTriple,
+    /// A Swordfish with fins.
+    FinnedSwordfish,
+    /// A finned Swordfish that is incomplete without the fins.
+    SashimiSwordfish,
+    /// A cell with {x, y} sees two cells with {x, z} and {y, z}.
+    XYWing,
+    /// A cell with {x, y, z} sees two cells with {x, z} and {y, z}.
+    XYZWing,
+    /// Two cells with the same two answer_candidate are connected by a strong link.
+    WWing,
+    /// Three corners of a rectangle hold only the same two answer_candidate.
+    UniqueRectangleType1,
+    /// The cells connected by the strong links of an answer_candidate are colored with two colors.
+    SimpleColoring,
+    /// The other two corners of a rectangle hold the same one extra answer_candidate.
+    UniqueRectangleType2,
+    /// The extra answer_candidate of the other two corners of a rectangle form a naked subset.
+    UniqueRectangleType3,
+    /// One of the two answer_candidate is held only by the other two corners of a rectangle in a group.
+    UniqueRectangleType4,
+    /// Four cells in a group hold only the same four answer_candidate.
+    NakedQuad,
+    /// An answer_candidate in four rows (columns) is held only by the cells in the same four columns (rows).
+    Jellyfish,
+    /// Four answer_candidate are held only by the same four cells in a group.
+    HiddenQuad,
+    /// A Jellyfish with fins.
+    FinnedJellyfish,
+    /// A finned Jellyfish that is incomplete without the fins.
+    SashimiJellyfish,
+    /// All the unanswered cells have two answer_candidate except one cell with three.
+    BugPlusOne,
+    /// An alternating chain of strong and weak links on an answer_candidate.
+    XChain,
+    /// A chain of cells with two answer_candidate.
+    XYChain,
+    /// Assuming one of the two answer_candidate of a cell, the singles lead to a contradiction.
+    TrialAndError,
+}
+
+impl Technique {
+    /// All the techniques that do not assume the uniqueness of the solution, in ascending order of score.
+    pub const ALL: [Technique; 26] = [
+        Technique::HiddenSingle,
+        Technique::NakedSingle,
+        Technique::Pointing,
+        Technique::Claiming,
+        Technique::NakedPair,
+        Technique::XWing,
+        Technique::HiddenPair,
+        Technique::FinnedXWing,
+        Technique::NakedTriple,
+        Technique::SashimiXWing,
+        Technique::Swordfish,
+        Technique::HiddenTriple,
+        Technique::FinnedSwordfish,
+        Technique::SashimiSwordfish,
+        Technique::XYWing,
+        Technique::XYZWing,
+        Technique::WWing,
+        Technique::SimpleColoring,
+        Technique::NakedQuad,
+        Technique::Jellyfish,
+        Technique::HiddenQuad,
+        Technique::FinnedJellyfish,
+        Technique::SashimiJellyfish,
+        Technique::XChain,
+        Technique::XYChain,
+        Technique::TrialAndError,
+    ];
+
+    /// The techniques that assume the game has only one solution, in ascending order of score.
+    /// They are used only when the caller declares that the game has only one solution.
+    ///
+    /// ゲームの解答がただ 1 つであることを前提とするテクニック。スコアの昇順に並んでいる。
+    /// 呼び出し側がゲームの解答がただ 1 つであると宣言した場合にのみ使用する。
+    pub const UNIQUENESS: [Technique; 5] = [
+        Technique::UniqueRectangleType1,
+        Technique::UniqueRectangleType2,
+        Technique::UniqueRectangleType3,
+        Technique::UniqueRectangleType4,
+        Technique::BugPlusOne,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Technique::HiddenSingle => "Hidden Single",
+            Technique::NakedSingle => "Naked Single",
+            Technique::Pointing => "Pointing",
+            Technique::Claiming => "Claiming",
+            Technique::NakedPair => "Naked Pair",
+            Technique::XWing => "X-Wing",
+            Technique::HiddenPair => "Hidden Pair",
+            Technique::FinnedXWing => "Finned X-Wing",
+            Technique::NakedTriple => "Naked Triple",
+            Technique::SashimiXWing => "Sashimi X-Wing",
+            Technique::Swordfish => "Swordfish",
+            Technique::HiddenTriple => "Hidden Triple",
+            Technique::FinnedSwordfish => "Finned Swordfish",
+            Technique::SashimiSwordfish => "Sashimi Swordfish",
+            Technique::XYWing => "XY-Wing",
+            Technique::XYZWing => "XYZ-Wing",
+            Technique::WWing => "W-Wing",
+            Technique::UniqueRectangleType1 => "Unique Rectangle Type 1",
+            Technique::SimpleColoring => "Simple Coloring",
+            Technique::UniqueRectangleType2 => "Unique Rectangle Type 2",
+            Technique::UniqueRectangleType3 => "Unique Rectangle Type 3",
+            Technique::UniqueRectangleType4 => "Unique Rectangle Type 4",
+            Technique::NakedQuad => "Naked Quad",
+            Technique::Jellyfish => "Jellyfish",
+            Technique::HiddenQuad => "Hidden Quad",
+            Technique::FinnedJellyfish => "Finned Jellyfish",
+            Technique::SashimiJellyfish => "Sashimi Jellyfish",
+            Technique::BugPlusOne => "BUG+1",
+            Technique::XChain => "X-Chain",
+            Technique::XYChain => "XY-Chain",
+            Technique::TrialAndError => "Trial and Error",
+        }
+    }
+
+    /// Returns the difficulty of the games that require this technique.
+    ///
+    /// このテクニックを必要とするゲームの難易度を返却する。
+    pub fn difficulty(&self) -> Difficulty {
+        match self {
+            Technique::HiddenSingle => Difficulty::Easy,
+            Technique::NakedSingle => Difficulty::Medium,
+            Technique::Pointing => Difficulty::Medium,
+            Technique::Claiming => Difficulty::Medium,
+            Technique::NakedPair => Difficulty::Medium,
+            Technique::XWing => Difficulty::Hard,
+            Technique::HiddenPair => Difficulty::Medium,
+            Technique::FinnedXWing => Difficulty::Hard,
+            Technique::NakedTriple => Difficulty::Hard,
+            Technique::SashimiXWing => Difficulty::Hard,
+            Technique::Swordfish => Difficulty::Hard,
+            Technique::HiddenTriple => Difficulty::Hard,
+            Technique::FinnedSwordfish => Difficulty::Hard,
+            Technique::SashimiSwordfish => Difficulty::Hard,
+            Technique::XYWing => Difficulty::Hard,
+            Technique::XYZWing => Difficulty::Hard,
+            Technique::WWing => Difficulty::Hard,
+            Technique::UniqueRectangleType1 => Difficulty::Hard,
+            Technique::SimpleColoring => Difficulty::Hard,
+            Technique::UniqueRectangleType2 => Difficulty::Hard,
+            Technique::UniqueRectangleType3 => Difficulty::Hard,
+            Technique::UniqueRectangleType4 => Difficulty::Hard,
+            Technique::NakedQuad => Difficulty::Hard,
+            Technique::Jellyfish => Difficulty::Hard,
+            Technique::HiddenQuad => Difficulty::Hard,
+            Technique::FinnedJellyfish => Difficulty::Hard,
+            Technique::SashimiJellyfish => Difficulty::Hard,
+            Technique::BugPlusOne => Difficulty::Hard,
+            Technique::XChain => Difficulty::Expert,
+            Technique::XYChain => Difficulty::Expert,
+            Technique::TrialAndError => Difficulty::Expert,
+        }
+    }
+
+    /// Returns the score of this technique, on a scale similar to the Sudoku Explainer rating.
+    ///
+    /// Sudoku Explainer のレーティングに近い尺度で、このテクニックのスコアを返却する。
+    pub fn score(&self) -> f32 {
+        match self {
+            Technique::HiddenSingle => 1.5,
+            Technique::NakedSingle => 2.3,
+            Technique::Pointing => 2.6,
+            Technique::Claiming => 2.8,
+            Technique::NakedPair => 3.0,
+            Technique::XWing => 3.2,
+            Technique::HiddenPair => 3.4,
+            Technique::FinnedXWing => 3.5,
+            Technique::NakedTriple => 3.6,
+            Technique::SashimiXWing => 3.7,
+            Technique::Swordfish => 3.8,
+            Technique::HiddenTriple => 4.0,
+            Technique::FinnedSwordfish => 4.1,
+            Technique::SashimiSwordfish => 4.2,
+            Technique::XYWing => 4.2,
+            Technique::XYZWing => 4.4,
+            Technique::WWing => 4.5,
+            Technique::UniqueRectangleType1 => 4.5,
+            Technique::SimpleColoring => 4.6,
+            Technique::UniqueRectangleType2 => 4.6,
+            Technique::UniqueRectangleType3 => 4.7,
+            Technique::UniqueRectangleType4 => 4.8,
+            Technique::NakedQuad => 5.0,
+            Technique::Jellyfish => 5.2,
+            Technique::HiddenQuad => 5.4,
+            Technique::FinnedJellyfish => 5.5,
+            Technique::SashimiJellyfish => 5.6,
+            Technique::BugPlusOne => 5.6,
+            Technique::XChain => 6.5,
+            Technique::XYChain => 6.6,
+            Technique::TrialAndError => 7.5,
+        }
+    }
+
+    /// Returns true if the technique assumes that the game has only one solution.
+    ///
+    /// テクニックがゲームの解答がただ 1 つであることを前提とする場合に true を返す。
+    pub fn requires_unique_solution(&self) -> bool {
+        Technique::UNIQUENESS.contains(self)
+    }
+
+    /// All the techniques including the ones that assume the uniqueness of the solution, in ascending order of score.
+    ///
+    /// 解答がただ 1 つであることを前提とするものも含めた全てのテクニック。スコアの昇順に並んでいる。
+    pub fn all_with_uniqueness() -> Vec<Technique> {
+        let mut techniques: Vec<Technique> = Technique::ALL
+            .iter()
+            .chain(Technique::UNIQUENESS.iter())
+            .copied()
+            .collect();
+        techniques.sort();
+        techniques
+    }
+
+    /// Find a step of this technique that can be applied to the game.
+    ///
+    /// ゲームに適用できるこのテクニックのステップを探す。
+    pub fn find(&self, game: &NormalGame) -> Option<Step> {
+        match self {
+            Technique::HiddenSingle => single::find_hidden_single(game),
+            Technique::NakedSingle => single::find_naked_single(game),
+            Technique::Pointing => intersection::find_pointing(game),
+            Technique::Claiming => intersection::find_claiming(game),
+            Technique::NakedPair => subset::find_naked_subset(game, 2),
+            Technique::XWing => fish::find_fish(game, 2, Technique::XWing),
+            Technique::HiddenPair => subset::find_hidden_subset(game, 2),
+            Technique::FinnedXWing => fish::find_fish(game, 2, Technique::FinnedXWing),
+            Technique::NakedTriple => subset::find_naked_subset(game, 3),
+            Technique::SashimiXWing => fish::find_fish(game, 2, Technique::SashimiXWing),
+            Technique::Swordfish => fish::find_fish(game, 3, Technique::Swordfish),
+            Technique::HiddenTriple => subset::find_hidden_subset(game, 3),
+            Technique::FinnedSwordfish => fish::find_fish(game, 3, Technique::FinnedSwordfish),
+            Technique::SashimiSwordfish => fish::find_fish(game, 3, Technique::SashimiSwordfish),
+            Technique::XYWing => chain::find_xy_wing(game),
+            Technique::XYZWing => chain::find_xyz_wing(game),
+            Technique::WWing => chain::find_w_wing(game),
+            Technique::UniqueRectangleType1 => uniqueness::find_unique_rectangle(game, *self),
+            Technique::SimpleColoring => chain::find_simple_coloring(game),
+            Technique::UniqueRectangleType2 => uniqueness::find_unique_rectangle(game, *self),
+            Technique::UniqueRectangleType3 => uniqueness::find_unique_rectangle(game, *self),
+            Technique::UniqueRectangleType4 => uniqueness::find_unique_rectangle(game, *self),
+            Technique::NakedQuad => subset::find_naked_subset(game, 4),
+            Technique::Jellyfish => fish::find_fish(game, 4, Technique::Jellyfish),
+            Technique::HiddenQuad => subset::find_hidden_subset(game, 4),
+            Technique::FinnedJellyfish => fish::find_fish(game, 4, Technique::FinnedJellyfish),
+            Technique::SashimiJellyfish => fish::find_fish(game, 4, Technique::SashimiJellyfish),
+            Technique::BugPlusOne => uniqueness::find_bug_plus_one(game),
+            Technique::XChain => chain::find_x_chain(game),
+            Technique::XYChain => chain::find_xy_chain(game),
+            Technique::TrialAndError => trial::find_trial_and_error(game),
+        }
+    }
+}
+
+/// A deduction made by a technique.
+/// `placements` are the answers to be set and `eliminations` are the answer_candidate to be removed.
+/// `cells`, `digits` and `groups` are the cells, the answer_candidate and the indexes of the groups that support the deduction.
+/// `chain` is the path of the chain that leads to the deduction, for the chain-based techniques.
+///
+/// テクニックによる推論。
+/// `placements` は設定する解答で、`eliminations` は除去する answer_candidate である。
+/// `cells`、`digits`、`groups` は推論の根拠となるセル、answer_candidate、グループのインデックスである。
+/// `chain` はチェーンを使うテクニックにおいて、推論を導くチェーンの経路である。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub technique: Technique,
+    pub placements: Vec<(Position, u8)>,
+    pub eliminations: Vec<(Position, u8)>,
+    pub cells: Vec<Position>,
+    pub digits: Vec<u8>,
+    pub groups: Vec<usize>,
+    pub chain: Vec<(Position, u8)>,
+}
+
+/// The result of solving a game only with techniques.
+///
+/// テクニックのみでゲームを解いた結果。
+pub struct LogicalSolution {
+    pub game: NormalGame,
+    pub steps: Vec<Step>,
+    pub state: GameState,
+}
+
+impl LogicalSolution {
+    pub fn is_solved(&self) -> bool {
+        self.state == GameState::Complete
+    }
+    /// Returns the technique with the highest score used.
+    pub fn hardest(&self) -> Option<Technique> {
+        self.steps
+            .iter()
+            .map(|s| s.technique)
+            .max_by(|a, b| a.score().partial_cmp(&b.score()).unwrap())
+    }
+}
+
+/// Returns all the combinations of the specified size, keeping the order of the items.
+///
+/// 要素の順序を保ったまま、指定したサイズの全ての組み合わせを返却する。
+fn combinations<T: Clone>(items: &[T], size: usize) -> Vec<Vec<T>> {
+    if size == 0 {
+        return vec![vec![]];
+    }
+    if items.len() < size {
+        return vec![];
+    }
+    let mut result = vec![];
+    for (i, item) in items.iter().enumerate() {
+        for mut rest in combinations(&items[i + 1..], size - 1) {
+            rest.insert(0, item.clone());
+            result.push(rest);
+        }
+    }
+    result
+}
+
+impl NormalGame {
+    /// Find a step of the first technique that can be applied, trying the techniques in the specified order.
+    ///
+    /// 指定した順にテクニックを試し、最初に適用できたテクニックのステップを返却する。
+    pub fn find_step(&self, techniques: &[Technique]) -> Option<Step> {
+        techniques.iter().find_map(|t| t.find(self))
+    }
+
+    /// Set the answers and remove the answer_candidate of the step.
+    ///
+    /// ステップの解答を設定し、answer_candidate を除去する。
+    pub fn apply_step(&mut self, step: &Step) {
+        for (pos, answer) in step.placements.iter() {
+            self.set_answer(*pos, *answer);
+        }
+        for (pos, candidate) in step.eliminations.iter() {
+            self.remove_answer_candidate(*pos, *candidate);
+        }
+    }
+
+    /// Solve the game only with the specified techniques, without guessing.
+    /// The easiest applicable technique is always applied first.
+    ///
+    /// 指定したテクニックのみで、推測をせずにゲームを解く。
+    /// 常に適用できる最も易しいテクニックから適用する。
+    pub fn solve_logically(&self, techniques: &[Technique]) -> LogicalSolution {
+        let mut techniques = techniques.to_vec();
+        techniques.sort();
+        let mut game = self.clone();
+        let mut steps: Vec<Step> = vec![];
+        let mut state = game.check_status();
+        while state == GameState::Solving {
+            let step = match game.find_step(&techniques) {
+                Some(step) => step,
+                None => break,
+            };
+            game.apply_step(&step);
+            steps.push(step);
+            state = game.check_status();
+        }
+        LogicalSolution { game, steps, state }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normal_game::setting::BlockSize;
+    use crate::normal_game::setting::GameSetting;
+    fn game(issue: &str) -> NormalGame {
+        let mut game = NormalGame::new(GameSetting::new(BlockSize {
+            height: 3,
+            width: 3,
+        }));
+        game.load(issue);
+        game
+    }
+    #[test]
+    fn it_solves_with_singles() {
+        let solution = game(" 7     6 |6   1   3|  54 87  |  8   4  | 1  3  5 |  9   1  |  35 12  |7   2   8| 5     9 ")
+            .solve_logically(&Technique::ALL);
+        assert!(solution.is_solved());
+        assert_eq!(solution.game.to_string(), "174392865|682715943|935468721|528176439|417839652|369254187|893541276|746923518|251687394");
+        assert_eq!(solution.steps.len(), 81 - 25);
+    }
+    #[test]
+    fn steps_are_consistent_with_the_solution() {
+        let issues = [
+            "   2  861|  5  1   |      4  |8  47    | 2 18 3  | 34      |  7      |9  5    2| 8 6  1   ",
+            " 13      |9    36  |4  98  1 |      9 5|8        |  7 6  23| 6       |  1 35  8| 4 8  5   ",
+            " 4  9 23 |    54  8|       4 |3   2 1  |   7     |5  8   76| 1      9|    1  6 |627  9  1 ",
+            "  5 8  93|  96  2  |2   5 7  |4      27|   96    |     1 3 |  85   7 | 93   8  |    4  5  ",
+            "4     8 5| 3       |   7     |  2     6 |    8 4  |    1    |   6 3 7 |5  2     |1 4      ",
+        ];
+        for issue in issues.iter() {
+            let mut game = game(issue);
+            let solved = game.solve().unwrap();
+            let answer = |pos: Position| solved.find_cell(pos).unwrap().answer().unwrap();
+            while game.check_status() == GameState::Solving {
+                let step = match game.find_step(&Technique::ALL) {
+                    Some(step) => step,
+                    None => break,
+                };
+                for (pos, digit) in step.placements.iter() {
+                    assert_eq!(answer(*pos), *digit, "{:?}", step);
+                }
+                for (pos, digit) in step.eliminations.iter() {
+                    assert_ne!(answer(*pos), *digit, "{:?}", step);
+                }
+                game.apply_step(&step);
+            }
+        }
+    }
+    #[test]
+    fn combinations_keep_the_order() {
+        assert_eq!(
+            combinations(&[1, 2, 3, 4], 2),
+            vec![
+                vec![1, 2],
+                vec![1, 3],
+                vec![1, 4],
+                vec![2, 3],
+                vec![2, 4],
+                vec![3, 4]
+            ]
+        );
+        assert!(combinations(&[1, 2], 3).is_empty());
+    }
+    #[test]
+    fn it_stops_when_no_technique_applies() {
+        let solution = game("").solve_logically(&Technique::ALL);
+        assert!(!solution.is_solved());
+        assert!(solution.steps.is_empty());
+    }
+    #[test]
+    fn it_uses_only_the_specified_techniques() {
+        let solution = game(" 7     6 |6   1   3|  54 87  |  8   4  | 1  3  5 |  9   1  |  35 12  |7   2   8| 5     9 ")
+            .solve_logically(&[Technique::HiddenSingle]);
+        assert!(solution
+            .steps
+            .iter()
+            .all(|s| s.technique == Technique::HiddenSingle));
+    }
+}
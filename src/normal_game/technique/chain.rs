@@ -0,0 +1,562 @@
+use crate::normal_game::cell::Position;
+use crate::normal_game::technique::link::LinkGraph;
+use crate::normal_game::technique::link::Node;
+use crate::normal_game::technique::Step;
+use crate::normal_game::technique::Technique;
+use crate::normal_game::NormalGame;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+/// Find a pivot cell with answer_candidate {x, y} and two pincer cells with {x, z} and {y, z} that see the pivot.
+/// Either pincer is z, so z can be removed from the cells that see both pincers.
+///
+/// answer_candidate が {x, y} のピボットのセルと、ピボットから見える {x, z} と {y, z} の 2 つのピンサーのセルを探す。
+/// いずれかのピンサーが z となるため、両方のピンサーから見えるセルから z を除去できる。
+pub(super) fn find_xy_wing(game: &NormalGame) -> Option<Step> {
+    for pivot in unanswered_with_count(game, 2) {
+        let xy: Vec<u8> = game.cells()[pivot].candidates().iter().collect();
+        let pincers: Vec<usize> = game
+            .peers_of(pivot)
+            .iter()
+            .copied()
+            .filter(|p| is_unanswered_with_count(game, *p, 2))
+            .filter(|p| shared_candidates(game, pivot, *p).len() == 1)
+            .collect();
+        for (n, p1) in pincers.iter().enumerate() {
+            for p2 in pincers.iter().skip(n + 1) {
+                let x = shared_candidates(game, pivot, *p1)[0];
+                let y = shared_candidates(game, pivot, *p2)[0];
+                if x == y {
+                    continue;
+                }
+                let z = match shared_candidates(game, *p1, *p2).first() {
+                    Some(z) if !xy.contains(z) => *z,
+                    _ => continue,
+                };
+                let eliminations = eliminations_seen_by(game, &[*p1, *p2], z);
+                if !eliminations.is_empty() {
+                    return Some(Step {
+                        technique: Technique::XYWing,
+                        placements: vec![],
+                        eliminations,
+                        cells: positions(game, &[pivot, *p1, *p2]),
+                        digits: vec![x, y, z],
+                        groups: vec![],
+                        chain: to_chain(
+                            game,
+                            &[
+                                (*p1, z),
+                                (*p1, x),
+                                (pivot, x),
+                                (pivot, y),
+                                (*p2, y),
+                                (*p2, z),
+                            ],
+                        ),
+                    });
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Find a pivot cell with answer_candidate {x, y, z} and two pincer cells with {x, z} and {y, z} that see the pivot.
+/// One of the three cells is z, so z can be removed from the cells that see all of them.
+///
+/// answer_candidate が {x, y, z} のピボットのセルと、ピボットから見える {x, z} と {y, z} の 2 つのピンサーのセルを探す。
+/// 3 つのセルのいずれかが z となるため、全てのセルから見えるセルから z を除去できる。
+pub(super) fn find_xyz_wing(game: &NormalGame) -> Option<Step> {
+    for pivot in unanswered_with_count(game, 3) {
+        let xyz = game.cells()[pivot].candidates();
+        let pincers: Vec<usize> = game
+            .peers_of(pivot)
+            .iter()
+            .copied()
+            .filter(|p| is_unanswered_with_count(game, *p, 2))
+            .filter(|p| game.cells()[*p].candidates().difference(xyz).is_empty())
+            .collect();
+        for (n, p1) in pincers.iter().enumerate() {
+            for p2 in pincers.iter().skip(n + 1) {
+                let shared = shared_candidates(game, *p1, *p2);
+                if shared.len() != 1 {
+                    continue;
+                }
+                let z = shared[0];
+                let eliminations = eliminations_seen_by(game, &[pivot, *p1, *p2], z);
+                if !eliminations.is_empty() {
+                    return Some(Step {
+                        technique: Technique::XYZWing,
+                        placements: vec![],
+                        eliminations,
+                        cells: positions(game, &[pivot, *p1, *p2]),
+                        digits: xyz.iter().collect(),
+                        groups: vec![],
+                        chain: to_chain(game, &[(*p1, z), (pivot, z), (*p2, z)]),
+                    });
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Find two cells with the same answer_candidate {x, y} that are connected by a strong link on x.
+/// Either cell is y, so y can be removed from the cells that see both cells.
+///
+/// 同じ answer_candidate {x, y} を持ち、x の強いリンクで繋がった 2 つのセルを探す。
+/// いずれかのセルが y となるため、両方のセルから見えるセルから y を除去できる。
+pub(super) fn find_w_wing(game: &NormalGame) -> Option<Step> {
+    let graph = LinkGraph::new(game);
+    let bivalues = unanswered_with_count(game, 2);
+    for (n, a) in bivalues.iter().enumerate() {
+        for b in bivalues.iter().skip(n + 1) {
+            if game.cells()[*a].candidates() != game.cells()[*b].candidates()
+                || game.peers_of(*a).contains(b)
+            {
+                continue;
+            }
+            let xy: Vec<u8> = game.cells()[*a].candidates().iter().collect();
+            for (x, y) in [(xy[0], xy[1]), (xy[1], xy[0])].iter() {
+                let eliminations = eliminations_seen_by(game, &[*a, *b], *y);
+                if eliminations.is_empty() {
+                    continue;
+                }
+                let link = game
+                    .peers_of(*a)
+                    .iter()
+                    .filter(|c1| *c1 != b)
+                    .flat_map(|c1| {
+                        graph
+                            .strong_links((*c1, *x))
+                            .iter()
+                            .map(move |(c2, _)| (*c1, *c2))
+                    })
+                    .find(|(c1, c2)| {
+                        c2 != a && c2 != b && *c1 != *c2 && game.peers_of(*b).contains(c2)
+                    });
+                if let Some((c1, c2)) = link {
+                    return Some(Step {
+                        technique: Technique::WWing,
+                        placements: vec![],
+                        eliminations,
+                        cells: positions(game, &[*a, *b, c1, c2]),
+                        digits: vec![*x, *y],
+                        groups: vec![],
+                        chain: to_chain(
+                            game,
+                            &[(*a, *y), (*a, *x), (c1, *x), (c2, *x), (*b, *x), (*b, *y)],
+                        ),
+                    });
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Find an alternating chain of strong and weak links on a single answer_candidate, that starts and ends with a strong link.
+/// Either end of the chain is true, so the answer_candidate can be removed from the cells that see both ends.
+///
+/// 1 つの answer_candidate について、強いリンクで始まり強いリンクで終わる、強いリンクと弱いリンクの交互のチェーンを探す。
+/// チェーンの両端のいずれかが真となるため、両端から見えるセルからその answer_candidate を除去できる。
+pub(super) fn find_x_chain(game: &NormalGame) -> Option<Step> {
+    let graph = LinkGraph::new(game);
+    for start in unanswered(game) {
+        for candidate in game.cells()[start].candidates().iter() {
+            let same_digit = |a: Node, b: Node| a.1 == b.1;
+            let mut eliminations = vec![];
+            let chain = graph.find_alternating_chain(
+                (start, candidate),
+                3,
+                same_digit,
+                same_digit,
+                |chain| {
+                    let end = chain[chain.len() - 1].0;
+                    eliminations = eliminations_seen_by(game, &[start, end], candidate);
+                    !eliminations.is_empty()
+                },
+            );
+            if let Some(chain) = chain {
+                return Some(chain_step(game, Technique::XChain, chain, eliminations));
+            }
+        }
+    }
+    None
+}
+
+/// Find a chain of cells with two answer_candidate, in which the adjacent cells see each other and share an answer_candidate,
+/// and both ends hold the same answer_candidate z that is not used in the links at the ends.
+/// Either end is z, so z can be removed from the cells that see both ends.
+///
+/// answer_candidate が 2 つのセルのチェーンで、隣り合うセルが互いに見えて answer_candidate を共有し、
+/// 両端がリンクに使われていない同じ answer_candidate z を持つものを探す。
+/// 両端のいずれかが z となるため、両端から見えるセルから z を除去できる。
+pub(super) fn find_xy_chain(game: &NormalGame) -> Option<Step> {
+    let graph = LinkGraph::new(game);
+    let in_cell = |a: Node, b: Node| a.0 == b.0;
+    let between_bivalues =
+        |a: Node, b: Node| a.0 != b.0 && a.1 == b.1 && is_unanswered_with_count(game, b.0, 2);
+    for start in unanswered_with_count(game, 2) {
+        for candidate in game.cells()[start].candidates().iter() {
+            let mut eliminations = vec![];
+            let chain = graph.find_alternating_chain(
+                (start, candidate),
+                5,
+                in_cell,
+                between_bivalues,
+                |chain| {
+                    let end = chain[chain.len() - 1];
+                    if end.1 != candidate {
+                        return false;
+                    }
+                    eliminations = eliminations_seen_by(game, &[start, end.0], candidate);
+                    !eliminations.is_empty()
+                },
+            );
+            if let Some(chain) = chain {
+                return Some(chain_step(game, Technique::XYChain, chain, eliminations));
+            }
+        }
+    }
+    None
+}
+
+/// Color the cells connected by the strong links of an answer_candidate with two colors, alternately.
+/// If two cells of the same color see each other, the color is false and the answer_candidate is removed from the cells of the color.
+/// Otherwise, one of the colors is true, so the answer_candidate is removed from the cells that see both colors.
+///
+/// answer_candidate の強いリンクで繋がったセルを 2 色で交互に塗る。
+/// 同じ色の 2 つのセルが互いに見える場合はその色が偽となり、その色のセルから answer_candidate を除去する。
+/// それ以外の場合はいずれかの色が真となるため、両方の色から見えるセルから answer_candidate を除去する。
+pub(super) fn find_simple_coloring(game: &NormalGame) -> Option<Step> {
+    let graph = LinkGraph::new(game);
+    for candidate in game.setting().candidates().iter() {
+        let mut colored: HashMap<usize, bool> = HashMap::new();
+        for start in unanswered(game) {
+            if colored.contains_key(&start) || !game.cells()[start].has_answer_candidate(candidate)
+            {
+                continue;
+            }
+            let component = color(&graph, start, candidate);
+            if component.len() < 3 {
+                colored.extend(component);
+                continue;
+            }
+            let of_color = |c: bool| -> Vec<usize> {
+                component
+                    .iter()
+                    .filter(|(_, color)| *color == c)
+                    .map(|(i, _)| *i)
+                    .collect()
+            };
+            let (on, off) = (of_color(true), of_color(false));
+            let eliminations: Vec<(Position, u8)> = match [&on, &off]
+                .iter()
+                .find(|cells| sees_each_other(game, cells))
+            {
+                Some(wrapped) => wrapped
+                    .iter()
+                    .map(|i| (game.cells()[*i].pos(), candidate))
+                    .collect(),
+                None => unanswered(game)
+                    .into_iter()
+                    .filter(|i| component.iter().all(|(c, _)| c != i))
+                    .filter(|i| game.cells()[*i].has_answer_candidate(candidate))
+                    .filter(|i| {
+                        on.iter().any(|c| game.peers_of(*i).contains(c))
+                            && off.iter().any(|c| game.peers_of(*i).contains(c))
+                    })
+                    .map(|i| (game.cells()[i].pos(), candidate))
+                    .collect(),
+            };
+            if !eliminations.is_empty() {
+                let cells: Vec<usize> = component.iter().map(|(i, _)| *i).collect();
+                return Some(Step {
+                    technique: Technique::SimpleColoring,
+                    placements: vec![],
+                    eliminations,
+                    cells: positions(game, &cells),
+                    digits: vec![candidate],
+                    groups: vec![],
+                    chain: cells
+                        .iter()
+                        .map(|i| (game.cells()[*i].pos(), candidate))
+                        .collect(),
+                });
+            }
+            colored.extend(component);
+        }
+    }
+    None
+}
+
+/// Returns the cells connected to the start by the strong links with their colors, in the order they are colored.
+fn color(graph: &LinkGraph, start: usize, candidate: u8) -> Vec<(usize, bool)> {
+    let mut component = vec![(start, true)];
+    let mut queue = VecDeque::from(vec![(start, true)]);
+    while let Some((cell, c)) = queue.pop_front() {
+        for (next, digit) in graph.strong_links((cell, candidate)) {
+            if *digit == candidate && component.iter().all(|(i, _)| i != next) {
+                component.push((*next, !c));
+                queue.push_back((*next, !c));
+            }
+        }
+    }
+    component
+}
+
+fn sees_each_other(game: &NormalGame, cells: &[usize]) -> bool {
+    cells
+        .iter()
+        .any(|a| cells.iter().any(|b| game.peers_of(*a).contains(b)))
+}
+
+fn chain_step(
+    game: &NormalGame,
+    technique: Technique,
+    chain: Vec<Node>,
+    eliminations: Vec<(Position, u8)>,
+) -> Step {
+    let mut cells: Vec<usize> = chain.iter().map(|(i, _)| *i).collect();
+    cells.dedup();
+    let mut digits: Vec<u8> = chain.iter().map(|(_, d)| *d).collect();
+    digits.sort_unstable();
+    digits.dedup();
+    Step {
+        technique,
+        placements: vec![],
+        eliminations,
+        cells: positions(game, &cells),
+        digits,
+        groups: vec![],
+        chain: to_chain(game, &chain),
+    }
+}
+
+/// Returns the answer_candidate to be removed from the other cells that see all the specified cells.
+fn eliminations_seen_by(game: &NormalGame, cells: &[usize], candidate: u8) -> Vec<(Position, u8)> {
+    game.peers_of(cells[0])
+        .iter()
+        .copied()
+        .filter(|i| !cells.contains(i))
+        .filter(|i| game.cells()[*i].has_answer_candidate(candidate))
+        .filter(|i| cells.iter().skip(1).all(|c| game.peers_of(*c).contains(i)))
+        .map(|i| (game.cells()[i].pos(), candidate))
+        .collect()
+}
+
+fn unanswered(game: &NormalGame) -> Vec<usize> {
+    (0..game.cells().len())
+        .filter(|i| game.cells()[*i].answer().is_none())
+        .collect()
+}
+
+fn unanswered_with_count(game: &NormalGame, count: usize) -> Vec<usize> {
+    (0..game.cells().len())
+        .filter(|i| is_unanswered_with_count(game, *i, count))
+        .collect()
+}
+
+fn is_unanswered_with_count(game: &NormalGame, index: usize, count: usize) -> bool {
+    let cell = &game.cells()[index];
+    cell.answer().is_none() && cell.answer_candidate_count() == count
+}
+
+fn shared_candidates(game: &NormalGame, a: usize, b: usize) -> Vec<u8> {
+    game.cells()[a]
+        .candidates()
+        .intersection(game.cells()[b].candidates())
+        .iter()
+        .collect()
+}
+
+fn positions(game: &NormalGame, cells: &[usize]) -> Vec<Position> {
+    cells.iter().map(|i| game.cells()[*i].pos()).collect()
+}
+
+fn to_chain(game: &NormalGame, nodes: &[Node]) -> Vec<(Position, u8)> {
+    nodes
+        .iter()
+        .map(|(i, d)| (game.cells()[*i].pos(), *d))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normal_game::setting::BlockSize;
+    use crate::normal_game::setting::GameSetting;
+    fn game() -> NormalGame {
+        NormalGame::new(GameSetting::new(BlockSize {
+            height: 3,
+            width: 3,
+        }))
+    }
+    fn keep(game: &mut NormalGame, x: u8, y: u8, candidates: &[u8]) {
+        for candidate in 1..=9 {
+            if !candidates.contains(&candidate) {
+                game.remove_answer_candidate(Position::new(x, y), candidate);
+            }
+        }
+    }
+    /// Remove the answer_candidate from the cells except the specified cells.
+    fn remove_except(game: &mut NormalGame, cells: &[(u8, u8)], all: &[(u8, u8)], candidate: u8) {
+        for (x, y) in all.iter() {
+            if !cells.contains(&(*x, *y)) {
+                game.remove_answer_candidate(Position::new(*x, *y), candidate);
+            }
+        }
+    }
+    fn column(x: u8) -> Vec<(u8, u8)> {
+        (0..9).map(|y| (x, y)).collect()
+    }
+    fn row(y: u8) -> Vec<(u8, u8)> {
+        (0..9).map(|x| (x, y)).collect()
+    }
+    fn sorted(mut eliminations: Vec<(Position, u8)>) -> Vec<(Position, u8)> {
+        eliminations.sort_by_key(|(p, _)| (p.y(), p.x()));
+        eliminations
+    }
+    mod wing {
+        use super::*;
+        #[test]
+        fn it_finds_xy_wing() {
+            let mut game = game();
+            keep(&mut game, 0, 0, &[1, 2]);
+            keep(&mut game, 4, 0, &[1, 3]);
+            keep(&mut game, 0, 4, &[2, 3]);
+            let step = find_xy_wing(&game).unwrap();
+            assert_eq!(step.technique, Technique::XYWing);
+            assert_eq!(step.eliminations, vec![(Position::new(4, 4), 3)]);
+            assert_eq!(step.chain.first(), Some(&(Position::new(4, 0), 3)));
+            assert_eq!(step.chain.last(), Some(&(Position::new(0, 4), 3)));
+        }
+        #[test]
+        fn it_finds_xyz_wing() {
+            let mut game = game();
+            keep(&mut game, 0, 0, &[1, 2, 3]);
+            keep(&mut game, 1, 1, &[1, 3]);
+            keep(&mut game, 5, 0, &[2, 3]);
+            assert!(find_xy_wing(&game).is_none());
+            let step = find_xyz_wing(&game).unwrap();
+            assert_eq!(step.technique, Technique::XYZWing);
+            assert_eq!(
+                step.eliminations,
+                vec![(Position::new(1, 0), 3), (Position::new(2, 0), 3)]
+            );
+        }
+        #[test]
+        fn it_finds_w_wing() {
+            // (0, 0) and (4, 4) are {1, 2}, and 1 in the row 8 is only at (0, 8) or (4, 8).
+            let mut game = game();
+            keep(&mut game, 0, 0, &[1, 2]);
+            keep(&mut game, 4, 4, &[1, 2]);
+            remove_except(&mut game, &[(0, 8), (4, 8)], &row(8), 1);
+            let step = find_w_wing(&game).unwrap();
+            assert_eq!(step.technique, Technique::WWing);
+            assert_eq!(
+                step.eliminations,
+                vec![(Position::new(4, 0), 2), (Position::new(0, 4), 2)]
+            );
+            assert_eq!(
+                step.chain[2..4],
+                [(Position::new(0, 8), 1), (Position::new(4, 8), 1)]
+            );
+        }
+    }
+    mod chain {
+        use super::*;
+        #[test]
+        fn it_finds_x_chain() {
+            // (0, 0) = (0, 6) - (4, 6) = (4, 1) on 1.
+            let mut game = game();
+            remove_except(&mut game, &[(0, 0), (0, 6)], &column(0), 1);
+            remove_except(&mut game, &[(4, 1), (4, 6)], &column(4), 1);
+            let step = find_x_chain(&game).unwrap();
+            assert_eq!(step.technique, Technique::XChain);
+            assert_eq!(
+                step.chain,
+                vec![
+                    (Position::new(0, 0), 1),
+                    (Position::new(0, 6), 1),
+                    (Position::new(4, 6), 1),
+                    (Position::new(4, 1), 1)
+                ]
+            );
+            assert_eq!(
+                sorted(step.eliminations),
+                vec![
+                    (Position::new(3, 0), 1),
+                    (Position::new(5, 0), 1),
+                    (Position::new(1, 1), 1),
+                    (Position::new(2, 1), 1)
+                ]
+            );
+        }
+        #[test]
+        fn it_finds_xy_chain() {
+            let mut game = game();
+            keep(&mut game, 0, 0, &[1, 2]);
+            keep(&mut game, 4, 0, &[2, 3]);
+            keep(&mut game, 4, 4, &[3, 4]);
+            keep(&mut game, 8, 4, &[4, 1]);
+            assert!(find_xy_wing(&game).is_none());
+            let step = find_xy_chain(&game).unwrap();
+            assert_eq!(step.technique, Technique::XYChain);
+            assert_eq!(step.chain.len(), 8);
+            assert_eq!(step.digits, vec![1, 2, 3, 4]);
+            assert_eq!(
+                sorted(step.eliminations),
+                vec![(Position::new(8, 0), 1), (Position::new(0, 4), 1)]
+            );
+        }
+        #[test]
+        fn it_finds_nothing_in_empty_game() {
+            assert!(find_x_chain(&game()).is_none());
+            assert!(find_xy_chain(&game()).is_none());
+        }
+    }
+    mod coloring {
+        use super::*;
+        #[test]
+        fn it_finds_cells_that_see_both_colors() {
+            // (0, 0) and (4, 4) are one color, and (0, 4) and (4, 1) are the other.
+            let mut game = game();
+            remove_except(&mut game, &[(0, 0), (0, 4)], &column(0), 1);
+            remove_except(&mut game, &[(0, 4), (4, 4)], &row(4), 1);
+            remove_except(&mut game, &[(4, 4), (4, 1)], &column(4), 1);
+            let step = find_simple_coloring(&game).unwrap();
+            assert_eq!(step.technique, Technique::SimpleColoring);
+            assert_eq!(step.cells.len(), 4);
+            assert_eq!(
+                sorted(step.eliminations),
+                vec![
+                    (Position::new(3, 0), 1),
+                    (Position::new(5, 0), 1),
+                    (Position::new(1, 1), 1),
+                    (Position::new(2, 1), 1)
+                ]
+            );
+        }
+        #[test]
+        fn it_removes_the_color_that_sees_itself() {
+            // (0, 0) = (0, 4) = (4, 4) = (4, 1) = (1, 1) on 1.
+            // (0, 0), (4, 4) and (1, 1) are the same color, and (0, 0) sees (1, 1).
+            let mut game = game();
+            remove_except(&mut game, &[(0, 0), (0, 4)], &column(0), 1);
+            remove_except(&mut game, &[(0, 4), (4, 4)], &row(4), 1);
+            remove_except(&mut game, &[(4, 4), (4, 1)], &column(4), 1);
+            remove_except(&mut game, &[(4, 1), (1, 1)], &row(1), 1);
+            let step = find_simple_coloring(&game).unwrap();
+            assert_eq!(
+                sorted(step.eliminations),
+                vec![
+                    (Position::new(0, 0), 1),
+                    (Position::new(1, 1), 1),
+                    (Position::new(4, 4), 1)
+                ]
+            );
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use crate::normal_game::cell::Position;
+use crate::normal_game::group::GroupKind;
+use crate::normal_game::technique::combinations;
+use crate::normal_game::technique::Step;
+use crate::normal_game::technique::Technique;
+use crate::normal_game::NormalGame;
+
+/// Find a fish of the technique, whose base lines are `size` rows (or columns).
+///
+/// If an answer_candidate in the base lines is held only by the cells in `size` cover lines,
+/// the answer_candidate can be removed from the other cells in the cover lines.
+/// A finned fish has extra cells, called fins, outside the cover lines.
+/// Then, the answer_candidate can be removed only from the cells that also see all the fins.
+/// A sashimi fish is a finned fish that would be incomplete without the fins.
+///
+/// 基本となる `size` 個の行 (列) を持つ、指定したテクニックのフィッシュを探す。
+///
+/// 基本となる行で answer_candidate を保有するセルが `size` 個の列のみにある場合、
+/// その列の他のセルから answer_candidate を除去できる。
+/// フィン付きのフィッシュは、その列の外にフィンと呼ばれる余分なセルを持つ。
+/// その場合、全てのフィンから見えるセルからのみ answer_candidate を除去できる。
+/// サシミのフィッシュは、フィンがなければ不完全となるフィン付きのフィッシュである。
+pub(super) fn find_fish(game: &NormalGame, size: usize, technique: Technique) -> Option<Step> {
+    find_fish_in(game, size, technique, GroupKind::Row, GroupKind::Column)
+        .or_else(|| find_fish_in(game, size, technique, GroupKind::Column, GroupKind::Row))
+}
+
+fn find_fish_in(
+    game: &NormalGame,
+    size: usize,
+    technique: Technique,
+    base_kind: GroupKind,
+    cover_kind: GroupKind,
+) -> Option<Step> {
+    // The fins must be seen from a cell in a cover line, so they are in the columns (rows) of one block.
+    let setting = game.setting();
+    let max_fins = if technique == fish_technique(size, 0, false) {
+        0
+    } else if base_kind == GroupKind::Row {
+        setting.block_width() as usize
+    } else {
+        setting.block_height() as usize
+    };
+    for candidate in setting.candidates().iter() {
+        let lines: Vec<(usize, Vec<usize>)> = game
+            .groups()
+            .iter()
+            .enumerate()
+            .filter(|(_, g)| g.kind() == base_kind && g.candidates().contains(candidate))
+            .map(|(g, group)| {
+                let holders: Vec<usize> = group
+                    .cells()
+                    .iter()
+                    .copied()
+                    .filter(|i| game.cells()[*i].has_answer_candidate(candidate))
+                    .collect();
+                (g, holders)
+            })
+            .filter(|(_, holders)| holders.len() >= 2 && holders.len() <= size + max_fins)
+            .collect();
+        for base in combinations(&lines, size) {
+            let holders: Vec<usize> = base.iter().flat_map(|(_, h)| h.clone()).collect();
+            let mut covers: Vec<usize> = holders
+                .iter()
+                .map(|i| group_of_kind(game, *i, cover_kind))
+                .collect();
+            covers.sort_unstable();
+            covers.dedup();
+            if covers.len() > size + max_fins {
+                continue;
+            }
+            for cover in combinations(&covers, size) {
+                let fins: Vec<usize> = holders
+                    .iter()
+                    .copied()
+                    .filter(|i| !cover.contains(&group_of_kind(game, *i, cover_kind)))
+                    .collect();
+                let covered_counts: Vec<usize> = base
+                    .iter()
+                    .map(|(_, h)| h.iter().filter(|i| !fins.contains(i)).count())
+                    .collect();
+                if covered_counts.contains(&0) {
+                    continue;
+                }
+                let sashimi = covered_counts.iter().any(|c| *c < 2);
+                if fish_technique(size, fins.len(), sashimi) != technique {
+                    continue;
+                }
+                let eliminations: Vec<(Position, u8)> = cover
+                    .iter()
+                    .flat_map(|g| game.groups()[*g].cells().iter().copied())
+                    .filter(|i| !holders.contains(i))
+                    .filter(|i| game.cells()[*i].has_answer_candidate(candidate))
+                    .filter(|i| fins.iter().all(|f| game.peers_of(*i).contains(f)))
+                    .map(|i| (game.cells()[i].pos(), candidate))
+                    .collect();
+                if !eliminations.is_empty() {
+                    return Some(Step {
+                        technique,
+                        placements: vec![],
+                        eliminations,
+                        cells: holders.iter().map(|i| game.cells()[*i].pos()).collect(),
+                        digits: vec![candidate],
+                        groups: base.iter().map(|(g, _)| *g).chain(cover).collect(),
+                        chain: vec![],
+                    });
+                }
+            }
+        }
+    }
+    None
+}
+
+fn group_of_kind(game: &NormalGame, index: usize, kind: GroupKind) -> usize {
+    *game
+        .groups_of(index)
+        .iter()
+        .find(|g| game.groups()[**g].kind() == kind)
+        .unwrap()
+}
+
+fn fish_technique(size: usize, fin_count: usize, sashimi: bool) -> Technique {
+    match (size, fin_count, sashimi) {
+        (2, 0, _) => Technique::XWing,
+        (2, _, false) => Technique::FinnedXWing,
+        (2, _, true) => Technique::SashimiXWing,
+        (3, 0, _) => Technique::Swordfish,
+        (3, _, false) => Technique::FinnedSwordfish,
+        (3, _, true) => Technique::SashimiSwordfish,
+        (_, 0, _) => Technique::Jellyfish,
+        (_, _, false) => Technique::FinnedJellyfish,
+        (_, _, true) => Technique::SashimiJellyfish,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normal_game::setting::BlockSize;
+    use crate::normal_game::setting::GameSetting;
+    fn game(height: u8, width: u8) -> NormalGame {
+        NormalGame::new(GameSetting::new(BlockSize { height, width }))
+    }
+    /// Leave the answer_candidate only in the specified columns of the row.
+    fn keep_in_row(game: &mut NormalGame, y: u8, xs: &[u8], candidate: u8) {
+        for x in 0..game.setting().side_size() {
+            if !xs.contains(&x) {
+                game.remove_answer_candidate(Position::new(x, y), candidate);
+            }
+        }
+    }
+    mod basic {
+        use super::*;
+        #[test]
+        fn it_finds_x_wing() {
+            let mut game = game(3, 3);
+            keep_in_row(&mut game, 0, &[1, 6], 1);
+            keep_in_row(&mut game, 4, &[1, 6], 1);
+            let step = find_fish(&game, 2, Technique::XWing).unwrap();
+            assert_eq!(step.technique, Technique::XWing);
+            assert_eq!(step.digits, vec![1]);
+            assert_eq!(step.groups, vec![0, 4, 9 + 1, 9 + 6]);
+            assert_eq!(step.eliminations.len(), 7 * 2);
+            assert!(step
+                .eliminations
+                .iter()
+                .all(|(p, _)| (p.x() == 1 || p.x() == 6) && p.y() != 0 && p.y() != 4));
+        }
+        #[test]
+        fn it_finds_x_wing_in_columns_of_16x16() {
+            let mut game = game(4, 4);
+            for y in 0..16 {
+                if y != 2 && y != 13 {
+                    game.remove_answer_candidate(Position::new(3, y), 5);
+                    game.remove_answer_candidate(Position::new(10, y), 5);
+                }
+            }
+            let step = find_fish(&game, 2, Technique::XWing).unwrap();
+            assert_eq!(step.groups, vec![16 + 3, 16 + 10, 2, 13]);
+            assert_eq!(step.eliminations.len(), 14 * 2);
+        }
+        #[test]
+        fn it_finds_swordfish() {
+            let mut game = game(3, 3);
+            keep_in_row(&mut game, 0, &[0, 4], 1);
+            keep_in_row(&mut game, 3, &[4, 8], 1);
+            keep_in_row(&mut game, 6, &[0, 8], 1);
+            assert!(find_fish(&game, 2, Technique::XWing).is_none());
+            let step = find_fish(&game, 3, Technique::Swordfish).unwrap();
+            assert_eq!(step.eliminations.len(), 6 * 3);
+        }
+        #[test]
+        fn it_finds_jellyfish() {
+            let mut game = game(3, 3);
+            keep_in_row(&mut game, 0, &[0, 2], 1);
+            keep_in_row(&mut game, 2, &[2, 4], 1);
+            keep_in_row(&mut game, 4, &[4, 6], 1);
+            keep_in_row(&mut game, 6, &[6, 0], 1);
+            assert!(find_fish(&game, 3, Technique::Swordfish).is_none());
+            let step = find_fish(&game, 4, Technique::Jellyfish).unwrap();
+            assert_eq!(step.eliminations.len(), 5 * 4);
+        }
+        #[test]
+        fn it_finds_nothing_in_empty_game() {
+            assert!(find_fish(&game(3, 3), 2, Technique::XWing).is_none());
+            assert!(find_fish(&game(3, 3), 2, Technique::FinnedXWing).is_none());
+        }
+    }
+    mod finned {
+        use super::*;
+        #[test]
+        fn it_finds_finned_x_wing() {
+            // The fin at (7, 1) is in the same block as (6, 0) and (6, 2).
+            let mut game = game(3, 3);
+            keep_in_row(&mut game, 1, &[1, 6, 7], 1);
+            keep_in_row(&mut game, 4, &[1, 6], 1);
+            assert!(find_fish(&game, 2, Technique::XWing).is_none());
+            let step = find_fish(&game, 2, Technique::FinnedXWing).unwrap();
+            assert_eq!(step.technique, Technique::FinnedXWing);
+            assert_eq!(
+                step.eliminations,
+                vec![(Position::new(6, 0), 1), (Position::new(6, 2), 1)]
+            );
+        }
+        #[test]
+        fn it_finds_sashimi_x_wing() {
+            let mut game = game(3, 3);
+            keep_in_row(&mut game, 1, &[6, 7], 1);
+            keep_in_row(&mut game, 4, &[1, 6], 1);
+            assert!(find_fish(&game, 2, Technique::FinnedXWing).is_none());
+            let step = find_fish(&game, 2, Technique::SashimiXWing).unwrap();
+            assert_eq!(step.technique, Technique::SashimiXWing);
+            assert_eq!(
+                step.eliminations,
+                vec![(Position::new(6, 0), 1), (Position::new(6, 2), 1)]
+            );
+        }
+        #[test]
+        fn it_finds_finned_swordfish_in_2x3() {
+            // The fin at (1, 1) is in the same block as (0, 0).
+            let mut game = game(2, 3);
+            keep_in_row(&mut game, 1, &[0, 1, 3], 1);
+            keep_in_row(&mut game, 3, &[3, 5], 1);
+            keep_in_row(&mut game, 5, &[0, 5], 1);
+            let step = find_fish(&game, 3, Technique::FinnedSwordfish).unwrap();
+            assert_eq!(step.eliminations, vec![(Position::new(0, 0), 1)]);
+        }
+    }
+}
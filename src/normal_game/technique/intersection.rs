@@ -0,0 +1,173 @@
+use crate::normal_game::cell::Position;
+use crate::normal_game::group::GroupKind;
+use crate::normal_game::technique::Step;
+use crate::normal_game::technique::Technique;
+use crate::normal_game::NormalGame;
+
+/// Find an answer_candidate in a block that is held only by the cells in one row or column.
+/// The answer_candidate can be removed from the other cells in the row or column.
+///
+/// ブロック内で 1 つの行または列のセルのみが保有している answer_candidate を探す。
+/// その answer_candidate は行または列の他のセルから除去できる。
+pub(super) fn find_pointing(game: &NormalGame) -> Option<Step> {
+    find_locked_candidate(
+        game,
+        Technique::Pointing,
+        |kind| kind == GroupKind::Block,
+        |kind| kind == GroupKind::Row || kind == GroupKind::Column,
+    )
+}
+
+/// Find an answer_candidate in a row or column that is held only by the cells in one block.
+/// The answer_candidate can be removed from the other cells in the block.
+///
+/// 行または列の中で 1 つのブロックのセルのみが保有している answer_candidate を探す。
+/// その answer_candidate はブロックの他のセルから除去できる。
+pub(super) fn find_claiming(game: &NormalGame) -> Option<Step> {
+    find_locked_candidate(
+        game,
+        Technique::Claiming,
+        |kind| kind == GroupKind::Row || kind == GroupKind::Column,
+        |kind| kind == GroupKind::Block,
+    )
+}
+
+/// Find an answer_candidate of a `from` group whose cells all belong to a `to` group,
+/// and remove it from the other cells of the `to` group.
+/// Since the groups are compared by their cells, it works for any shape of blocks.
+fn find_locked_candidate(
+    game: &NormalGame,
+    technique: Technique,
+    from: impl Fn(GroupKind) -> bool,
+    to: impl Fn(GroupKind) -> bool,
+) -> Option<Step> {
+    let groups = game.groups();
+    for (f, from_group) in groups.iter().enumerate().filter(|(_, g)| from(g.kind())) {
+        for candidate in from_group.candidates().iter() {
+            let holders: Vec<usize> = from_group
+                .cells()
+                .iter()
+                .copied()
+                .filter(|i| game.cells()[*i].has_answer_candidate(candidate))
+                .collect();
+            if holders.len() < 2 {
+                continue;
+            }
+            for (t, to_group) in groups.iter().enumerate().filter(|(_, g)| to(g.kind())) {
+                if !holders.iter().all(|i| to_group.cells().contains(i)) {
+                    continue;
+                }
+                let eliminations: Vec<(Position, u8)> = to_group
+                    .cells()
+                    .iter()
+                    .filter(|i| !from_group.cells().contains(i))
+                    .map(|i| &game.cells()[*i])
+                    .filter(|c| c.has_answer_candidate(candidate))
+                    .map(|c| (c.pos(), candidate))
+                    .collect();
+                if !eliminations.is_empty() {
+                    return Some(Step {
+                        technique,
+                        placements: vec![],
+                        eliminations,
+                        cells: holders.iter().map(|i| game.cells()[*i].pos()).collect(),
+                        digits: vec![candidate],
+                        groups: vec![f, t],
+                        chain: vec![],
+                    });
+                }
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normal_game::setting::BlockSize;
+    use crate::normal_game::setting::GameSetting;
+    fn game(height: u8, width: u8) -> NormalGame {
+        NormalGame::new(GameSetting::new(BlockSize { height, width }))
+    }
+    mod pointing {
+        use super::*;
+        #[test]
+        fn it_finds_pointing_to_row_in_2x3() {
+            // [1][1][1][ ][ ][ ]
+            // [ ][ ][ ][ ][ ][ ] 1 in the first block is in the row 0, so it is removed from the rest of the row.
+            let mut game = game(2, 3);
+            for x in 0..3 {
+                game.remove_answer_candidate(Position::new(x, 1), 1);
+            }
+            let step = find_pointing(&game).unwrap();
+            assert_eq!(step.technique, Technique::Pointing);
+            assert_eq!(step.digits, vec![1]);
+            assert_eq!(step.groups, vec![12, 0]);
+            assert_eq!(
+                step.eliminations,
+                vec![
+                    (Position::new(3, 0), 1),
+                    (Position::new(4, 0), 1),
+                    (Position::new(5, 0), 1)
+                ]
+            );
+        }
+        #[test]
+        fn it_finds_pointing_to_column_in_4x5() {
+            let mut game = game(4, 5);
+            for y in 0..4 {
+                for x in 1..5 {
+                    game.remove_answer_candidate(Position::new(x, y), 1);
+                }
+            }
+            let step = find_pointing(&game).unwrap();
+            assert_eq!(step.cells.len(), 4);
+            assert!(step.cells.iter().all(|p| p.x() == 0));
+            assert_eq!(step.eliminations.len(), 20 - 4);
+            assert!(step
+                .eliminations
+                .iter()
+                .all(|(p, d)| p.x() == 0 && p.y() >= 4 && *d == 1));
+        }
+        #[test]
+        fn it_finds_nothing_in_empty_game() {
+            assert!(find_pointing(&game(3, 3)).is_none());
+        }
+    }
+    mod claiming {
+        use super::*;
+        #[test]
+        fn it_finds_claiming_from_row_in_2x3() {
+            // [2][2][2][ ][ ][ ]
+            // [ ][ ][ ][ ][ ][ ] 2 in the row 0 is in the first block, so it is removed from the rest of the block.
+            let mut game = game(2, 3);
+            for x in 3..6 {
+                game.remove_answer_candidate(Position::new(x, 0), 2);
+            }
+            let step = find_claiming(&game).unwrap();
+            assert_eq!(step.technique, Technique::Claiming);
+            assert_eq!(step.digits, vec![2]);
+            assert_eq!(step.groups, vec![0, 12]);
+            assert_eq!(
+                step.eliminations,
+                vec![
+                    (Position::new(0, 1), 2),
+                    (Position::new(1, 1), 2),
+                    (Position::new(2, 1), 2)
+                ]
+            );
+        }
+        #[test]
+        fn it_finds_claiming_from_column_in_3x2() {
+            let mut game = game(3, 2);
+            for y in 3..6 {
+                game.remove_answer_candidate(Position::new(1, y), 4);
+            }
+            let step = find_claiming(&game).unwrap();
+            assert_eq!(step.groups, vec![6 + 1, 12]);
+            assert_eq!(step.eliminations.len(), 3);
+            assert!(step.eliminations.iter().all(|(p, _)| p.x() == 0));
+        }
+    }
+}
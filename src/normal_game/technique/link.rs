@@ -0,0 +1,198 @@
+use crate::normal_game::NormalGame;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+/// A candidate in the link graph, which is the index of the cell and the answer_candidate.
+///
+/// リンクグラフの候補。セルのインデックスと answer_candidate の組である。
+pub type Node = (usize, u8);
+
+/// The graph of the links between the candidates.
+///
+/// Two candidates are strongly linked if at least one of them is true.
+/// It happens when a group has only two cells that hold the answer_candidate,
+/// or when a cell has only two answer_candidate.
+/// Two candidates are weakly linked if at most one of them is true.
+/// It happens when the cells are peers and hold the same answer_candidate,
+/// or when they are in the same cell.
+///
+/// 候補間のリンクのグラフ。
+///
+/// 2 つの候補の少なくとも一方が真である場合、強いリンクで結ばれている。
+/// グループ内で answer_candidate を保有するセルが 2 つのみの場合や、セルの answer_candidate が 2 つのみの場合がこれにあたる。
+/// 2 つの候補の高々一方が真である場合、弱いリンクで結ばれている。
+/// 同じ answer_candidate を保有するセルがピアである場合や、同じセルの候補である場合がこれにあたる。
+pub struct LinkGraph<'a> {
+    game: &'a NormalGame,
+    strong: HashMap<Node, Vec<Node>>,
+}
+
+impl<'a> LinkGraph<'a> {
+    pub fn new(game: &'a NormalGame) -> LinkGraph<'a> {
+        let mut strong: HashMap<Node, Vec<Node>> = HashMap::new();
+        let mut add = |a: Node, b: Node| {
+            let links = strong.entry(a).or_default();
+            if !links.contains(&b) {
+                links.push(b);
+            }
+        };
+        for group in game.groups().iter() {
+            for candidate in group.candidates().iter() {
+                let holders: Vec<usize> = group
+                    .cells()
+                    .iter()
+                    .copied()
+                    .filter(|i| game.cells()[*i].has_answer_candidate(candidate))
+                    .collect();
+                if holders.len() == 2 {
+                    add((holders[0], candidate), (holders[1], candidate));
+                    add((holders[1], candidate), (holders[0], candidate));
+                }
+            }
+        }
+        for (i, cell) in game.cells().iter().enumerate() {
+            let candidates: Vec<u8> = cell.candidates().iter().collect();
+            if cell.answer().is_none() && candidates.len() == 2 {
+                add((i, candidates[0]), (i, candidates[1]));
+                add((i, candidates[1]), (i, candidates[0]));
+            }
+        }
+        LinkGraph { game, strong }
+    }
+
+    /// Returns the candidates strongly linked to the node.
+    ///
+    /// 指定した候補と強いリンクで結ばれた候補を返却する。
+    pub fn strong_links(&self, node: Node) -> &[Node] {
+        self.strong.get(&node).map(|v| v.as_slice()).unwrap_or(&[])
+    }
+
+    /// Returns the candidates weakly linked to the node.
+    ///
+    /// 指定した候補と弱いリンクで結ばれた候補を返却する。
+    pub fn weak_links(&self, node: Node) -> Vec<Node> {
+        let (index, candidate) = node;
+        let cell = &self.game.cells()[index];
+        self.game
+            .peers_of(index)
+            .iter()
+            .filter(|p| self.game.cells()[**p].has_answer_candidate(candidate))
+            .map(|p| (*p, candidate))
+            .chain(
+                cell.candidates()
+                    .iter()
+                    .filter(|c| *c != candidate)
+                    .map(|c| (index, c)),
+            )
+            .collect()
+    }
+
+    pub fn is_strong(&self, a: Node, b: Node) -> bool {
+        self.strong_links(a).contains(&b)
+    }
+
+    /// Find the shortest alternating chain that starts from the node with a strong link
+    /// and ends with a strong link at a node that satisfies `is_end`.
+    /// If the start node is false, the end node is true, so at least one of them is true.
+    /// `strong` and `weak` filter the links that can be used.
+    ///
+    /// 指定した候補から強いリンクで始まり、`is_end` を満たす候補で強いリンクで終わる最短の交互チェーンを探す。
+    /// 開始の候補が偽であれば終了の候補は真となるため、少なくとも一方は真である。
+    /// `strong` と `weak` で使用できるリンクを絞り込む。
+    pub fn find_alternating_chain(
+        &self,
+        start: Node,
+        min_links: usize,
+        strong: impl Fn(Node, Node) -> bool,
+        weak: impl Fn(Node, Node) -> bool,
+        mut is_end: impl FnMut(&[Node]) -> bool,
+    ) -> Option<Vec<Node>> {
+        // The state is the node and whether the last link is strong.
+        let mut parents: HashMap<(Node, bool), (Node, bool)> = HashMap::new();
+        let mut queue: VecDeque<((Node, bool), usize)> = VecDeque::new();
+        queue.push_back(((start, false), 0));
+        while let Some((state, length)) = queue.pop_front() {
+            let (node, last_strong) = state;
+            if last_strong && length >= min_links {
+                let chain = Self::to_chain(&parents, state);
+                if is_end(&chain) {
+                    return Some(chain);
+                }
+            }
+            let nexts: Vec<Node> = if last_strong {
+                self.weak_links(node)
+                    .into_iter()
+                    .filter(|n| weak(node, *n))
+                    .collect()
+            } else {
+                self.strong_links(node)
+                    .iter()
+                    .copied()
+                    .filter(|n| strong(node, *n))
+                    .collect()
+            };
+            for next in nexts {
+                let next_state = (next, !last_strong);
+                if next == start || parents.contains_key(&next_state) {
+                    continue;
+                }
+                parents.insert(next_state, state);
+                queue.push_back((next_state, length + 1));
+            }
+        }
+        None
+    }
+
+    fn to_chain(parents: &HashMap<(Node, bool), (Node, bool)>, end: (Node, bool)) -> Vec<Node> {
+        let mut chain = vec![end.0];
+        let mut state = end;
+        while let Some(parent) = parents.get(&state) {
+            chain.push(parent.0);
+            state = *parent;
+        }
+        chain.reverse();
+        chain
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normal_game::cell::Position;
+    use crate::normal_game::setting::BlockSize;
+    use crate::normal_game::setting::GameSetting;
+    fn game() -> NormalGame {
+        NormalGame::new(GameSetting::new(BlockSize {
+            height: 2,
+            width: 2,
+        }))
+    }
+    #[test]
+    fn it_links_conjugate_pairs_strongly() {
+        let mut game = game();
+        game.remove_answer_candidate(Position::new(1, 0), 1);
+        game.remove_answer_candidate(Position::new(2, 0), 1);
+        let graph = LinkGraph::new(&game);
+        assert!(graph.is_strong((0, 1), (3, 1)));
+        assert!(graph.is_strong((3, 1), (0, 1)));
+        assert!(!graph.is_strong((0, 2), (3, 2)));
+    }
+    #[test]
+    fn it_links_bivalue_cell_strongly() {
+        let mut game = game();
+        game.remove_answer_candidate(Position::new(0, 0), 1);
+        game.remove_answer_candidate(Position::new(0, 0), 2);
+        let graph = LinkGraph::new(&game);
+        assert_eq!(graph.strong_links((0, 3)), &[(0, 4)]);
+    }
+    #[test]
+    fn it_links_peers_weakly() {
+        let graph_game = game();
+        let graph = LinkGraph::new(&graph_game);
+        let weak = graph.weak_links((0, 1));
+        assert_eq!(weak.len(), 7 + 3);
+        assert!(weak.contains(&(5, 1)));
+        assert!(weak.contains(&(0, 4)));
+        assert!(!weak.contains(&(15, 1)));
+    }
+}
@@ -0,0 +1,81 @@
+use crate::normal_game::technique::Step;
+use crate::normal_game::technique::Technique;
+use crate::normal_game::NormalGame;
+
+/// Find an answer_candidate that is held by only one cell in a group.
+///
+/// グループ内で 1 つのセルのみが保有している answer_candidate を探す。
+pub(super) fn find_hidden_single(game: &NormalGame) -> Option<Step> {
+    game.groups().iter().enumerate().find_map(|(g, group)| {
+        group
+            .get_lonely(game.cells())
+            .first()
+            .map(|(pos, answer)| Step {
+                technique: Technique::HiddenSingle,
+                placements: vec![(*pos, *answer)],
+                eliminations: vec![],
+                cells: vec![*pos],
+                digits: vec![*answer],
+                groups: vec![g],
+                chain: vec![],
+            })
+    })
+}
+
+/// Find a cell that has only one answer_candidate.
+///
+/// answer_candidate を 1 つだけ持つセルを探す。
+pub(super) fn find_naked_single(game: &NormalGame) -> Option<Step> {
+    game.cells().iter().find_map(|c| {
+        c.get_lonely().map(|answer| Step {
+            technique: Technique::NakedSingle,
+            placements: vec![(c.pos(), answer)],
+            eliminations: vec![],
+            cells: vec![c.pos()],
+            digits: vec![answer],
+            groups: vec![],
+            chain: vec![],
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normal_game::cell::Position;
+    use crate::normal_game::setting::BlockSize;
+    use crate::normal_game::setting::GameSetting;
+    fn game() -> NormalGame {
+        NormalGame::new(GameSetting::new(BlockSize {
+            height: 2,
+            width: 2,
+        }))
+    }
+    #[test]
+    fn it_finds_hidden_single() {
+        let mut game = game();
+        // [1][2][ ][ ]
+        // [ ][ ][1][🌟]
+        // [ ][ ][ ][ ]
+        // [ ][ ][ ][ ] In the row 1, 2 can only be placed at 🌟.
+        game.load("12  |  1 ");
+        let step = find_hidden_single(&game).unwrap();
+        assert_eq!(step.technique, Technique::HiddenSingle);
+        assert_eq!(step.placements, vec![(Position::new(3, 1), 2)]);
+        assert_eq!(step.groups, vec![1]);
+    }
+    #[test]
+    fn it_finds_naked_single() {
+        let mut game = game();
+        game.load("12  |  3 ");
+        let step = find_naked_single(&game).unwrap();
+        assert_eq!(step.technique, Technique::NakedSingle);
+        assert_eq!(step.placements, vec![(Position::new(2, 0), 4)]);
+        assert_eq!(step.cells, vec![Position::new(2, 0)]);
+    }
+    #[test]
+    fn it_finds_nothing_in_empty_game() {
+        assert!(find_hidden_single(&game()).is_none());
+        assert!(find_naked_single(&game()).is_none());
+    }
+}
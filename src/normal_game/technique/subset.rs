@@ -0,0 +1,246 @@
+use crate::normal_game::candidates::Candidates;
+use crate::normal_game::cell::Position;
+use crate::normal_game::technique::combinations;
+use crate::normal_game::technique::Step;
+use crate::normal_game::technique::Technique;
+use crate::normal_game::NormalGame;
+
+/// Find `size` cells in a group that hold only the same `size` answer_candidate.
+/// The answer_candidate can be removed from the other cells in the group.
+///
+/// グループ内で `size` 個の answer_candidate のみを保有する `size` 個のセルを探す。
+/// その answer_candidate はグループ内の他のセルから除去できる。
+pub(super) fn find_naked_subset(game: &NormalGame, size: usize) -> Option<Step> {
+    for (g, group) in game.groups().iter().enumerate() {
+        let unanswered = unanswered_cells(game, group.cells());
+        let targets: Vec<usize> = unanswered
+            .iter()
+            .copied()
+            .filter(|i| {
+                let count = game.cells()[*i].answer_candidate_count();
+                count >= 2 && count <= size
+            })
+            .collect();
+        for subset in combinations(&targets, size) {
+            let digits = union_of_candidates(game, &subset);
+            if digits.len() != size {
+                continue;
+            }
+            let eliminations: Vec<(Position, u8)> = unanswered
+                .iter()
+                .filter(|i| !subset.contains(i))
+                .map(|i| &game.cells()[*i])
+                .flat_map(|c| {
+                    c.candidates()
+                        .intersection(digits)
+                        .iter()
+                        .map(move |d| (c.pos(), d))
+                })
+                .collect();
+            if !eliminations.is_empty() {
+                return Some(Step {
+                    technique: naked_technique(size),
+                    placements: vec![],
+                    eliminations,
+                    cells: subset.iter().map(|i| game.cells()[*i].pos()).collect(),
+                    digits: digits.iter().collect(),
+                    groups: vec![g],
+                    chain: vec![],
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Find `size` answer_candidate in a group that are held only by the same `size` cells.
+/// The other answer_candidate can be removed from those cells.
+///
+/// グループ内で同じ `size` 個のセルのみが保有する `size` 個の answer_candidate を探す。
+/// そのセルから他の answer_candidate を除去できる。
+pub(super) fn find_hidden_subset(game: &NormalGame, size: usize) -> Option<Step> {
+    for (g, group) in game.groups().iter().enumerate() {
+        let unanswered = unanswered_cells(game, group.cells());
+        let digits: Vec<u8> = group
+            .candidates()
+            .iter()
+            .filter(|d| {
+                let count = cells_holding(game, &unanswered, *d).len();
+                count >= 2 && count <= size
+            })
+            .collect();
+        for subset in combinations(&digits, size) {
+            let mut cells: Vec<usize> = subset
+                .iter()
+                .flat_map(|d| cells_holding(game, &unanswered, *d))
+                .collect();
+            cells.sort_unstable();
+            cells.dedup();
+            if cells.len() != size {
+                continue;
+            }
+            let subset_candidates: Candidates = subset.iter().collect();
+            let eliminations: Vec<(Position, u8)> = cells
+                .iter()
+                .map(|i| &game.cells()[*i])
+                .flat_map(|c| {
+                    c.candidates()
+                        .difference(subset_candidates)
+                        .iter()
+                        .map(move |d| (c.pos(), d))
+                })
+                .collect();
+            if !eliminations.is_empty() {
+                return Some(Step {
+                    technique: hidden_technique(size),
+                    placements: vec![],
+                    eliminations,
+                    cells: cells.iter().map(|i| game.cells()[*i].pos()).collect(),
+                    digits: subset,
+                    groups: vec![g],
+                    chain: vec![],
+                });
+            }
+        }
+    }
+    None
+}
+
+fn unanswered_cells(game: &NormalGame, cells: &[usize]) -> Vec<usize> {
+    cells
+        .iter()
+        .copied()
+        .filter(|i| game.cells()[*i].answer().is_none())
+        .collect()
+}
+
+fn cells_holding(game: &NormalGame, cells: &[usize], candidate: u8) -> Vec<usize> {
+    cells
+        .iter()
+        .copied()
+        .filter(|i| game.cells()[*i].has_answer_candidate(candidate))
+        .collect()
+}
+
+fn union_of_candidates(game: &NormalGame, cells: &[usize]) -> Candidates {
+    cells.iter().fold(Candidates::new(), |acc, i| {
+        acc.union(game.cells()[*i].candidates())
+    })
+}
+
+fn naked_technique(size: usize) -> Technique {
+    match size {
+        2 => Technique::NakedPair,
+        3 => Technique::NakedTriple,
+        _ => Technique::NakedQuad,
+    }
+}
+
+fn hidden_technique(size: usize) -> Technique {
+    match size {
+        2 => Technique::HiddenPair,
+        3 => Technique::HiddenTriple,
+        _ => Technique::HiddenQuad,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normal_game::setting::BlockSize;
+    use crate::normal_game::setting::GameSetting;
+    fn game() -> NormalGame {
+        NormalGame::new(GameSetting::new(BlockSize {
+            height: 3,
+            width: 3,
+        }))
+    }
+    fn keep(game: &mut NormalGame, pos: Position, candidates: &[u8]) {
+        for candidate in 1..=9 {
+            if !candidates.contains(&candidate) {
+                game.remove_answer_candidate(pos, candidate);
+            }
+        }
+    }
+    mod naked {
+        use super::*;
+        #[test]
+        fn it_finds_naked_pair() {
+            let mut game = game();
+            keep(&mut game, Position::new(0, 0), &[1, 2]);
+            keep(&mut game, Position::new(4, 0), &[1, 2]);
+            let step = find_naked_subset(&game, 2).unwrap();
+            assert_eq!(step.technique, Technique::NakedPair);
+            assert_eq!(step.cells, vec![Position::new(0, 0), Position::new(4, 0)]);
+            assert_eq!(step.digits, vec![1, 2]);
+            assert_eq!(step.groups, vec![0]);
+            assert_eq!(step.eliminations.len(), 7 * 2);
+            assert!(step.eliminations.contains(&(Position::new(8, 0), 2)));
+        }
+        #[test]
+        fn it_finds_naked_triple_with_two_candidates_in_each_cell() {
+            let mut game = game();
+            keep(&mut game, Position::new(0, 0), &[1, 2]);
+            keep(&mut game, Position::new(3, 0), &[2, 3]);
+            keep(&mut game, Position::new(6, 0), &[1, 3]);
+            assert!(find_naked_subset(&game, 2).is_none());
+            let step = find_naked_subset(&game, 3).unwrap();
+            assert_eq!(step.technique, Technique::NakedTriple);
+            assert_eq!(step.digits, vec![1, 2, 3]);
+            assert_eq!(step.eliminations.len(), 6 * 3);
+        }
+        #[test]
+        fn it_ignores_subset_without_eliminations() {
+            let mut game = game();
+            keep(&mut game, Position::new(0, 0), &[1, 2]);
+            keep(&mut game, Position::new(1, 0), &[1, 2]);
+            for x in 2..9 {
+                game.remove_answer_candidate(Position::new(x, 0), 1);
+                game.remove_answer_candidate(Position::new(x, 0), 2);
+            }
+            for y in 1..3 {
+                for x in 0..3 {
+                    game.remove_answer_candidate(Position::new(x, y), 1);
+                    game.remove_answer_candidate(Position::new(x, y), 2);
+                }
+            }
+            assert!(find_naked_subset(&game, 2).is_none());
+        }
+    }
+    mod hidden {
+        use super::*;
+        #[test]
+        fn it_finds_hidden_pair() {
+            let mut game = game();
+            for x in 2..9 {
+                game.remove_answer_candidate(Position::new(x, 0), 1);
+                game.remove_answer_candidate(Position::new(x, 0), 2);
+            }
+            let step = find_hidden_subset(&game, 2).unwrap();
+            assert_eq!(step.technique, Technique::HiddenPair);
+            assert_eq!(step.cells, vec![Position::new(0, 0), Position::new(1, 0)]);
+            assert_eq!(step.digits, vec![1, 2]);
+            assert_eq!(step.groups, vec![0]);
+            assert_eq!(step.eliminations.len(), 2 * 7);
+            assert!(!step.eliminations.iter().any(|(_, d)| *d == 1 || *d == 2));
+        }
+        #[test]
+        fn it_finds_hidden_quad() {
+            let mut game = game();
+            for x in 4..9 {
+                for d in 1..=4 {
+                    game.remove_answer_candidate(Position::new(x, 0), d);
+                }
+            }
+            let step = find_hidden_subset(&game, 4).unwrap();
+            assert_eq!(step.technique, Technique::HiddenQuad);
+            assert_eq!(step.digits, vec![1, 2, 3, 4]);
+            assert_eq!(step.eliminations.len(), 4 * 5);
+        }
+        #[test]
+        fn it_finds_nothing_in_empty_game() {
+            assert!(find_hidden_subset(&game(), 2).is_none());
+            assert!(find_naked_subset(&game(), 2).is_none());
+        }
+    }
+}
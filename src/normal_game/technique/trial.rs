@@ -0,0 +1,54 @@
+use crate::normal_game::technique::Step;
+use crate::normal_game::technique::Technique;
+use crate::normal_game::GameState;
+use crate::normal_game::NormalGame;
+
+/// Find an answer_candidate of a cell with two answer_candidate,
+/// that leads to a contradiction when it is assumed and the singles are filled in.
+///
+/// answer_candidate が 2 つのセルにおいて、仮に設定して唯一の解答を記入していくと矛盾する answer_candidate を探す。
+pub(super) fn find_trial_and_error(game: &NormalGame) -> Option<Step> {
+    for cell in game
+        .cells()
+        .iter()
+        .filter(|c| c.answer().is_none() && c.answer_candidate_count() == 2)
+    {
+        for candidate in cell.candidates().iter() {
+            let mut trial = game.clone();
+            trial.set_answer(cell.pos(), candidate);
+            if trial.fill_lonely() == GameState::Failure {
+                return Some(Step {
+                    technique: Technique::TrialAndError,
+                    placements: vec![],
+                    eliminations: vec![(cell.pos(), candidate)],
+                    cells: vec![cell.pos()],
+                    digits: vec![candidate],
+                    groups: vec![],
+                    chain: vec![],
+                });
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normal_game::setting::BlockSize;
+    use crate::normal_game::setting::GameSetting;
+    #[test]
+    fn it_eliminates_the_candidate_that_leads_to_contradiction() {
+        let mut game = NormalGame::new(GameSetting::new(BlockSize {
+            height: 3,
+            width: 3,
+        }));
+        game.load(
+            "5 2 9 1|   1   8|3    6  2| 4    7|6       1|  5    9|9  7    4| 6   3|  7 2 5 3",
+        );
+        let step = find_trial_and_error(&game).unwrap();
+        let (pos, candidate) = step.eliminations[0];
+        let solved = game.solve().unwrap();
+        assert_ne!(solved.find_cell(pos).unwrap().answer(), Some(candidate));
+    }
+}
@@ -0,0 +1,408 @@
+use crate::normal_game::candidates::Candidates;
+use crate::normal_game::cell::Position;
+use crate::normal_game::group::GroupKind;
+use crate::normal_game::technique::combinations;
+use crate::normal_game::technique::Step;
+use crate::normal_game::technique::Technique;
+use crate::normal_game::NormalGame;
+
+/// The techniques below assume that the game has only one solution.
+/// If four cells at the corners of a rectangle in two blocks could hold only the same two answer_candidate,
+/// the two answer_candidate could be swapped and the game would have two solutions.
+///
+/// 以下のテクニックはゲームの解答がただ 1 つであることを前提とする。
+/// 2 つのブロックにまたがる長方形の角の 4 つのセルが同じ 2 つの answer_candidate のみを持ちうる場合、
+/// その 2 つの answer_candidate を入れ替えることができ、ゲームの解答が 2 つになってしまう。
+///
+/// The swap may break the constraints of the variants, so they are not applied to the variants.
+///
+/// 入れ替えによってバリアントの制約を満たさなくなる場合があるため、バリアントには適用しない。
+pub(super) fn find_unique_rectangle(game: &NormalGame, technique: Technique) -> Option<Step> {
+    if game.setting().is_variant() {
+        return None;
+    }
+    let side_size = game.setting().side_size();
+    let ys: Vec<u8> = (0..side_size).collect();
+    for y in combinations(&ys, 2) {
+        for x in combinations(&ys, 2) {
+            let corners: Vec<usize> = [(x[0], y[0]), (x[1], y[0]), (x[0], y[1]), (x[1], y[1])]
+                .iter()
+                .map(|(x, y)| Position::new(*x, *y).index(side_size))
+                .collect();
+            if corners.iter().any(|i| game.cells()[*i].answer().is_some())
+                || block_count(game, &corners) != 2
+            {
+                continue;
+            }
+            let common = corners.iter().fold(game.setting().candidates(), |acc, i| {
+                acc.intersection(game.cells()[*i].candidates())
+            });
+            let digits: Vec<u8> = common.iter().collect();
+            for pair in combinations(&digits, 2) {
+                let found = match technique {
+                    Technique::UniqueRectangleType1 => find_type1(game, &corners, &pair),
+                    Technique::UniqueRectangleType2 => find_type2(game, &corners, &pair),
+                    Technique::UniqueRectangleType3 => find_type3(game, &corners, &pair),
+                    _ => find_type4(game, &corners, &pair),
+                };
+                if let Some((eliminations, cells, groups)) = found {
+                    let mut digits = pair.clone();
+                    digits.extend(
+                        cells
+                            .iter()
+                            .flat_map(|i| game.cells()[*i].candidates().iter()),
+                    );
+                    digits.sort_unstable();
+                    digits.dedup();
+                    return Some(Step {
+                        technique,
+                        placements: vec![],
+                        eliminations,
+                        cells: corners
+                            .iter()
+                            .chain(cells.iter())
+                            .map(|i| game.cells()[*i].pos())
+                            .collect(),
+                        digits,
+                        groups,
+                        chain: vec![],
+                    });
+                }
+            }
+        }
+    }
+    None
+}
+
+type Found = (Vec<(Position, u8)>, Vec<usize>, Vec<usize>);
+
+/// Three corners hold only the pair, so the pair can be removed from the other corner.
+fn find_type1(game: &NormalGame, corners: &[usize], pair: &[u8]) -> Option<Found> {
+    let pair_candidates: Candidates = pair.iter().collect();
+    let others: Vec<usize> = corners
+        .iter()
+        .copied()
+        .filter(|i| game.cells()[*i].candidates() != pair_candidates)
+        .collect();
+    if others.len() != 1 {
+        return None;
+    }
+    let eliminations = pair
+        .iter()
+        .map(|d| (game.cells()[others[0]].pos(), *d))
+        .collect();
+    Some((eliminations, vec![], vec![]))
+}
+
+/// Two corners in a row or column hold only the pair, and the other two hold the same one extra answer_candidate.
+/// The extra answer_candidate can be removed from the cells that see both of them.
+fn find_type2(game: &NormalGame, corners: &[usize], pair: &[u8]) -> Option<Found> {
+    let (roof, extra) = find_roof(game, corners, pair)?;
+    if game.cells()[roof[0]].candidates() != game.cells()[roof[1]].candidates() || extra.len() != 1
+    {
+        return None;
+    }
+    let candidate = extra.first().unwrap();
+    let eliminations: Vec<(Position, u8)> = game
+        .peers_of(roof[0])
+        .iter()
+        .copied()
+        .filter(|i| *i != roof[1] && game.peers_of(roof[1]).contains(i))
+        .filter(|i| game.cells()[*i].has_answer_candidate(candidate))
+        .map(|i| (game.cells()[i].pos(), candidate))
+        .collect();
+    if eliminations.is_empty() {
+        return None;
+    }
+    Some((eliminations, vec![], vec![]))
+}
+
+/// Two corners in a row or column hold only the pair, and the extra answer_candidate of the other two
+/// form a naked subset with other cells in a group that the two belong to.
+/// The answer_candidate of the subset can be removed from the rest of the group.
+fn find_type3(game: &NormalGame, corners: &[usize], pair: &[u8]) -> Option<Found> {
+    let (roof, extra) = find_roof(game, corners, pair)?;
+    for g in shared_groups(game, roof[0], roof[1]) {
+        let others: Vec<usize> = game.groups()[g]
+            .cells()
+            .iter()
+            .copied()
+            .filter(|i| !roof.contains(i) && game.cells()[*i].answer().is_none())
+            .collect();
+        let targets: Vec<usize> = others
+            .iter()
+            .copied()
+            .filter(|i| game.cells()[*i].answer_candidate_count() <= 4)
+            .collect();
+        for size in 1..=3 {
+            for subset in combinations(&targets, size) {
+                let union = subset
+                    .iter()
+                    .fold(extra, |acc, i| acc.union(game.cells()[*i].candidates()));
+                if union.len() != size + 1 {
+                    continue;
+                }
+                let eliminations: Vec<(Position, u8)> = others
+                    .iter()
+                    .filter(|i| !subset.contains(i))
+                    .map(|i| &game.cells()[*i])
+                    .flat_map(|c| {
+                        c.candidates()
+                            .intersection(union)
+                            .iter()
+                            .map(move |d| (c.pos(), d))
+                    })
+                    .collect();
+                if !eliminations.is_empty() {
+                    return Some((eliminations, subset, vec![g]));
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Two corners in a row or column hold only the pair, and one of the pair is held only by the other two corners in a group.
+/// The other answer_candidate of the pair can be removed from the two corners.
+fn find_type4(game: &NormalGame, corners: &[usize], pair: &[u8]) -> Option<Found> {
+    let (roof, _) = find_roof(game, corners, pair)?;
+    for g in shared_groups(game, roof[0], roof[1]) {
+        for (u, v) in [(pair[0], pair[1]), (pair[1], pair[0])].iter() {
+            let holders = game.groups()[g]
+                .cells()
+                .iter()
+                .filter(|i| game.cells()[**i].has_answer_candidate(*u))
+                .count();
+            if holders == 2 {
+                let eliminations = roof.iter().map(|i| (game.cells()[*i].pos(), *v)).collect();
+                return Some((eliminations, vec![], vec![g]));
+            }
+        }
+    }
+    None
+}
+
+/// Returns the two corners that hold extra answer_candidate and the union of the extras,
+/// if the other two corners hold only the pair and they are in a row or column.
+fn find_roof(
+    game: &NormalGame,
+    corners: &[usize],
+    pair: &[u8],
+) -> Option<(Vec<usize>, Candidates)> {
+    let pair_candidates: Candidates = pair.iter().collect();
+    let (floor, roof): (Vec<usize>, Vec<usize>) = corners
+        .iter()
+        .partition(|i| game.cells()[**i].candidates() == pair_candidates);
+    if floor.len() != 2 || shared_groups(game, floor[0], floor[1]).is_empty() {
+        return None;
+    }
+    let extra = roof.iter().fold(Candidates::new(), |acc, i| {
+        acc.union(game.cells()[*i].candidates().difference(pair_candidates))
+    });
+    Some((roof, extra))
+}
+
+/// Returns the indexes of the groups that both cells belong to.
+fn shared_groups(game: &NormalGame, a: usize, b: usize) -> Vec<usize> {
+    game.groups_of(a)
+        .iter()
+        .copied()
+        .filter(|g| game.groups_of(b).contains(g))
+        .collect()
+}
+
+fn block_count(game: &NormalGame, cells: &[usize]) -> usize {
+    let mut blocks: Vec<usize> = cells
+        .iter()
+        .flat_map(|i| game.groups_of(*i).iter().copied())
+        .filter(|g| game.groups()[*g].kind() == GroupKind::Block)
+        .collect();
+    blocks.sort_unstable();
+    blocks.dedup();
+    blocks.len()
+}
+
+/// Find the only cell with three answer_candidate, when all the other unanswered cells have two answer_candidate.
+/// Unless the answer_candidate that appears three times in a group of the cell is the answer,
+/// the game would have two solutions.
+///
+/// 解答のない他のセルが全て 2 つの answer_candidate を持つ場合に、3 つの answer_candidate を持つ唯一のセルを探す。
+/// そのセルのグループで 3 回現れる answer_candidate が解答でなければ、ゲームの解答が 2 つになってしまう。
+pub(super) fn find_bug_plus_one(game: &NormalGame) -> Option<Step> {
+    if game.setting().is_variant() {
+        return None;
+    }
+    let unanswered: Vec<usize> = (0..game.cells().len())
+        .filter(|i| game.cells()[*i].answer().is_none())
+        .collect();
+    let (triples, others): (Vec<usize>, Vec<usize>) = unanswered
+        .iter()
+        .partition(|i| game.cells()[**i].answer_candidate_count() == 3);
+    if triples.len() != 1
+        || others
+            .iter()
+            .any(|i| game.cells()[*i].answer_candidate_count() != 2)
+    {
+        return None;
+    }
+    let target = triples[0];
+    for g in game.groups_of(target).iter() {
+        let group = &game.groups()[*g];
+        for candidate in game.cells()[target].candidates().iter() {
+            let holders = group
+                .cells()
+                .iter()
+                .filter(|i| game.cells()[**i].has_answer_candidate(candidate))
+                .count();
+            if holders == 3 {
+                let pos = game.cells()[target].pos();
+                return Some(Step {
+                    technique: Technique::BugPlusOne,
+                    placements: vec![(pos, candidate)],
+                    eliminations: vec![],
+                    cells: vec![pos],
+                    digits: vec![candidate],
+                    groups: vec![*g],
+                    chain: vec![],
+                });
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normal_game::setting::BlockSize;
+    use crate::normal_game::setting::GameSetting;
+    fn game() -> NormalGame {
+        NormalGame::new(GameSetting::new(BlockSize {
+            height: 3,
+            width: 3,
+        }))
+    }
+    fn keep(game: &mut NormalGame, x: u8, y: u8, candidates: &[u8]) {
+        for candidate in game.setting().answer_candidate() {
+            if !candidates.contains(&candidate) {
+                game.remove_answer_candidate(Position::new(x, y), candidate);
+            }
+        }
+    }
+    mod unique_rectangle {
+        use super::*;
+        #[test]
+        fn it_finds_type1() {
+            let mut game = game();
+            keep(&mut game, 0, 0, &[1, 2]);
+            keep(&mut game, 3, 0, &[1, 2]);
+            keep(&mut game, 0, 1, &[1, 2]);
+            keep(&mut game, 3, 1, &[1, 2, 5]);
+            let step = find_unique_rectangle(&game, Technique::UniqueRectangleType1).unwrap();
+            assert_eq!(step.technique, Technique::UniqueRectangleType1);
+            assert_eq!(
+                step.eliminations,
+                vec![(Position::new(3, 1), 1), (Position::new(3, 1), 2)]
+            );
+        }
+        #[test]
+        fn it_ignores_variants() {
+            let mut game = NormalGame::new(
+                GameSetting::new(BlockSize {
+                    height: 3,
+                    width: 3,
+                })
+                .with_diagonals(),
+            );
+            keep(&mut game, 0, 0, &[1, 2]);
+            keep(&mut game, 3, 0, &[1, 2]);
+            keep(&mut game, 0, 1, &[1, 2]);
+            keep(&mut game, 3, 1, &[1, 2, 5]);
+            assert!(find_unique_rectangle(&game, Technique::UniqueRectangleType1).is_none());
+        }
+        #[test]
+        fn it_ignores_rectangle_in_four_blocks() {
+            let mut game = game();
+            keep(&mut game, 0, 0, &[1, 2]);
+            keep(&mut game, 3, 0, &[1, 2]);
+            keep(&mut game, 0, 3, &[1, 2]);
+            keep(&mut game, 3, 3, &[1, 2, 5]);
+            assert!(find_unique_rectangle(&game, Technique::UniqueRectangleType1).is_none());
+        }
+        #[test]
+        fn it_finds_type2() {
+            let mut game = game();
+            keep(&mut game, 0, 0, &[1, 2]);
+            keep(&mut game, 0, 1, &[1, 2]);
+            keep(&mut game, 3, 0, &[1, 2, 5]);
+            keep(&mut game, 3, 1, &[1, 2, 5]);
+            assert!(find_unique_rectangle(&game, Technique::UniqueRectangleType1).is_none());
+            let step = find_unique_rectangle(&game, Technique::UniqueRectangleType2).unwrap();
+            assert_eq!(step.eliminations.len(), 7 + 6);
+            assert!(step.eliminations.iter().all(|(_, d)| *d == 5));
+        }
+        #[test]
+        fn it_finds_type3() {
+            // The extras {5, 6} of (3, 0) and (3, 1) form a naked pair with (3, 5) in the column 3.
+            let mut game = game();
+            keep(&mut game, 0, 0, &[1, 2]);
+            keep(&mut game, 0, 1, &[1, 2]);
+            keep(&mut game, 3, 0, &[1, 2, 5]);
+            keep(&mut game, 3, 1, &[1, 2, 6]);
+            keep(&mut game, 3, 5, &[5, 6]);
+            assert!(find_unique_rectangle(&game, Technique::UniqueRectangleType2).is_none());
+            let step = find_unique_rectangle(&game, Technique::UniqueRectangleType3).unwrap();
+            assert_eq!(step.groups, vec![9 + 3]);
+            assert_eq!(step.eliminations.len(), 6 * 2);
+            assert!(step.eliminations.iter().all(|(p, _)| p.x() == 3));
+        }
+        #[test]
+        fn it_finds_type4() {
+            // In the column 3, 1 is held only by (3, 0) and (3, 1), so they cannot be 2.
+            let mut game = game();
+            keep(&mut game, 0, 0, &[1, 2]);
+            keep(&mut game, 0, 1, &[1, 2]);
+            keep(&mut game, 3, 0, &[1, 2, 5, 6]);
+            keep(&mut game, 3, 1, &[1, 2, 5, 6]);
+            for y in 2..9 {
+                game.remove_answer_candidate(Position::new(3, y), 1);
+            }
+            let step = find_unique_rectangle(&game, Technique::UniqueRectangleType4).unwrap();
+            assert_eq!(
+                step.eliminations,
+                vec![(Position::new(3, 0), 2), (Position::new(3, 1), 2)]
+            );
+        }
+    }
+    mod bug_plus_one {
+        use super::*;
+        #[test]
+        fn it_finds_the_answer_of_the_only_cell_with_three_candidates() {
+            // The candidates of each cell are the answers of two solutions, 1234|3412|2143|4321 and 2143|4321|1234|3412.
+            let mut game = NormalGame::new(GameSetting::new(BlockSize {
+                height: 2,
+                width: 2,
+            }));
+            let first = ["1234", "3412", "2143", "4321"];
+            let second = ["2143", "4321", "1234", "3412"];
+            for y in 0..4 {
+                for x in 0..4 {
+                    let a = first[y].as_bytes()[x] - b'0';
+                    let b = second[y].as_bytes()[x] - b'0';
+                    if (x, y) == (0, 0) {
+                        keep(&mut game, 0, 0, &[a, b, 3]);
+                    } else {
+                        keep(&mut game, x as u8, y as u8, &[a, b]);
+                    }
+                }
+            }
+            let step = find_bug_plus_one(&game).unwrap();
+            assert_eq!(step.technique, Technique::BugPlusOne);
+            assert_eq!(step.placements, vec![(Position::new(0, 0), 3)]);
+        }
+        #[test]
+        fn it_finds_nothing_in_empty_game() {
+            assert!(find_bug_plus_one(&game()).is_none());
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use crate::normal_game::cell::Position;
+use crate::normal_game::technique::Step;
+use crate::normal_game::technique::Technique;
+use crate::normal_game::GameState;
+use crate::normal_game::NormalGame;
+
+/// A step taken while solving the game.
+/// `depth` is the number of guesses that the step is based on.
+///
+/// ゲームを解く過程で行った手順。
+/// `depth` はその手順の前提となっている推測の数である。
+#[derive(Debug, Clone, PartialEq)]
+pub enum TraceStep {
+    /// A deduction made by a technique.
+    Deduction { step: Step, depth: usize },
+    /// The answer is assumed because no technique can be applied.
+    Guess {
+        pos: Position,
+        answer: u8,
+        depth: usize,
+    },
+    /// The guess led to a contradiction and was undone.
+    Backtrack {
+        pos: Position,
+        answer: u8,
+        depth: usize,
+    },
+}
+
+/// The result of `NormalGame::solve_with_trace`.
+///
+/// `NormalGame::solve_with_trace` の結果。
+#[derive(Clone)]
+pub struct SolveTrace {
+    pub solution: Option<NormalGame>,
+    pub steps: Vec<TraceStep>,
+}
+
+impl SolveTrace {
+    /// Returns the number of the guesses made.
+    pub fn guess_count(&self) -> usize {
+        self.steps
+            .iter()
+            .filter(|s| matches!(s, TraceStep::Guess { .. }))
+            .count()
+    }
+}
+
+impl NormalGame {
+    /// Solve the game like `solve`, and also return the ordered list of the steps taken.
+    /// The techniques are applied first, and when none can be applied, an answer is guessed like `assume_and_solve`.
+    /// The trace includes the guesses and the backtracks of the guesses that led to a contradiction.
+    ///
+    /// `solve` と同様にゲームを解き、行った手順の一覧も順に返却する。
+    /// まずテクニックを適用し、適用できるテクニックがない場合は `assume_and_solve` と同様に解答を推測する。
+    /// 手順には推測と、矛盾に至った推測の取り消しも含まれる。
+    pub fn solve_with_trace(&self) -> SolveTrace {
+        self.solve_with_trace_using(&Technique::ALL)
+    }
+
+    /// Solve the game with trace, using only the specified techniques before guessing.
+    ///
+    /// 推測の前に指定したテクニックのみを使用して、手順を記録しながらゲームを解く。
+    pub fn solve_with_trace_using(&self, techniques: &[Technique]) -> SolveTrace {
+        let mut techniques = techniques.to_vec();
+        techniques.sort();
+        let mut steps: Vec<TraceStep> = vec![];
+        let solution = Self::trace_solve(self.clone(), &techniques, 0, &mut steps);
+        SolveTrace { solution, steps }
+    }
+
+    fn trace_solve(
+        mut game: NormalGame,
+        techniques: &[Technique],
+        depth: usize,
+        steps: &mut Vec<TraceStep>,
+    ) -> Option<NormalGame> {
+        loop {
+            match game.check_status() {
+                GameState::Complete => return Some(game),
+                GameState::Failure => return None,
+                GameState::Solving => {}
+            }
+            match game.find_step(techniques) {
+                Some(step) => {
+                    game.apply_step(&step);
+                    steps.push(TraceStep::Deduction { step, depth });
+                }
+                None => break,
+            }
+        }
+        let cell = *game
+            .cells()
+            .iter()
+            .filter(|c| c.answer_candidate_count() != 0)
+            .min_by_key(|c| c.answer_candidate_count())?;
+        for answer in Self::ordered_answer_candidate(&game, cell.candidates()) {
+            let pos = cell.pos();
+            steps.push(TraceStep::Guess { pos, answer, depth });
+            let mut new_game = game.clone();
+            new_game.set_answer(pos, answer);
+            if let Some(solved) = Self::trace_solve(new_game, techniques, depth + 1, steps) {
+                return Some(solved);
+            }
+            steps.push(TraceStep::Backtrack { pos, answer, depth });
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normal_game::setting::BlockSize;
+    use crate::normal_game::setting::GameSetting;
+    fn game(issue: &str) -> NormalGame {
+        let mut game = NormalGame::new(GameSetting::new(BlockSize {
+            height: 3,
+            width: 3,
+        }));
+        game.load(issue);
+        game
+    }
+    #[test]
+    fn it_traces_the_deductions() {
+        let game = game(" 7     6 |6   1   3|  54 87  |  8   4  | 1  3  5 |  9   1  |  35 12  |7   2   8| 5     9 ");
+        let trace = game.solve_with_trace();
+        assert_eq!(
+            trace.solution.as_ref().unwrap().to_string(),
+            game.solve().unwrap().to_string()
+        );
+        assert_eq!(trace.guess_count(), 0);
+        assert_eq!(trace.steps.len(), 81 - 25);
+        assert!(trace
+            .steps
+            .iter()
+            .all(|s| matches!(s, TraceStep::Deduction { depth: 0, .. })));
+    }
+    #[test]
+    fn it_traces_the_guesses_and_the_backtracks() {
+        let game = game(" 4  9 23 |    54  8|       4 |3   2 1  |   7     |5  8   76| 1      9|    1  6 |627  9  1 ");
+        let trace = game.solve_with_trace_using(&[Technique::HiddenSingle, Technique::NakedSingle]);
+        assert_eq!(
+            trace.solution.as_ref().unwrap().to_string(),
+            game.solve().unwrap().to_string()
+        );
+        assert!(trace.guess_count() > 0);
+        let mut guesses: Vec<(Position, u8, usize)> = vec![];
+        let mut backtracked = false;
+        for step in trace.steps.iter() {
+            match step {
+                TraceStep::Guess { pos, answer, depth } => {
+                    assert_eq!(*depth, guesses.len());
+                    guesses.push((*pos, *answer, *depth));
+                }
+                TraceStep::Backtrack { pos, answer, depth } => {
+                    // The guesses at deeper levels have already been backtracked.
+                    guesses.truncate(depth + 1);
+                    assert_eq!(guesses.pop(), Some((*pos, *answer, *depth)));
+                    backtracked = true;
+                }
+                TraceStep::Deduction { depth, .. } => assert!(*depth <= guesses.len()),
+            }
+        }
+        assert!(backtracked);
+    }
+    #[test]
+    fn it_returns_no_solution_for_inconsistent_game() {
+        let trace = game("11").solve_with_trace();
+        assert!(trace.solution.is_none());
+    }
+}